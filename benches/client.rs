@@ -0,0 +1,188 @@
+//! Measures the client operations the CLI runs: `verify_client_message` and
+//! `update_state` on the `header.json` fixture and on generated chains with
+//! validator sets of growing size, and `verify_membership` on `proof.json`.
+//! Throughput is reported per header and per proof.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use ibc_client_tendermint::{
+    client_state::ClientState,
+    types::{ConsensusState, Header},
+};
+use ibc_core::{
+    channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+    client::{
+        context::client_state::{ClientStateCommon, ClientStateExecution, ClientStateValidation},
+        types::Height,
+    },
+    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+    host::types::{
+        identifiers::{ChainId, ChannelId, ClientId, PortId, Sequence},
+        path::{CommitmentPath, Path},
+    },
+    primitives::Timestamp,
+};
+use serde::Deserialize;
+use tendermint_lightclient::{
+    api::TendermintClient,
+    client_options::ClientOptions,
+    context::Ctx,
+    testgen::{ChainSpec, KeyType, ValidatorSpec},
+    utils::{base64_to_bytes, ConsensusStateFile},
+};
+
+const VALIDATOR_SET_SIZES: [usize; 4] = [4, 16, 64, 150];
+
+/// Client with the default parameters of the CLI.
+fn client_state(chain_id: &str, latest_height: Height) -> ClientState {
+    ClientOptions {
+        chain_id: ChainId::new(chain_id).unwrap(),
+        ..ClientOptions::default()
+    }
+    .client_state(latest_height)
+    .unwrap()
+    .into()
+}
+
+/// A client with its trusted consensus state and a header to update it with.
+struct Fixture {
+    client: ClientState,
+    cs: ConsensusState,
+    header: Header,
+}
+
+impl Fixture {
+    /// `header.json`, trusting `consensus_state.json` at height 0-6 of `chain2`,
+    /// the fixtures of the unit tests.
+    fn recorded() -> Self {
+        let header: Header = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/data/header.json"
+        )))
+        .unwrap();
+        let cs: ConsensusStateFile = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/data/consensus_state.json"
+        )))
+        .unwrap();
+        Self {
+            client: client_state("chain2", Height::new(0, 6).unwrap()),
+            cs: cs.into(),
+            header,
+        }
+    }
+
+    /// Header at height 3 of a generated chain signed by `validators`
+    /// validators, trusting height 1 so both validator sets are checked.
+    fn generated(validators: usize) -> Self {
+        let spec = ChainSpec {
+            length: 3,
+            validators: (0..validators)
+                .map(|i| ValidatorSpec {
+                    id: format!("v{i}"),
+                    power: 10,
+                    key_type: KeyType::Ed25519,
+                })
+                .collect(),
+            ..ChainSpec::default()
+        };
+        let chain = spec.generate().unwrap();
+        Self {
+            client: client_state(&spec.chain_id, chain.ibc_height(1).unwrap()),
+            cs: chain.consensus_state(1).unwrap(),
+            header: chain.header(3, 1).unwrap(),
+        }
+    }
+
+    fn ctx(&self, client_id: &ClientId) -> Ctx<TendermintClient> {
+        let mut ctx = Ctx::default();
+        self.client
+            .initialise(&mut ctx, client_id, self.cs.clone().into())
+            .unwrap();
+        ctx
+    }
+
+    fn bench(&self, c: &mut Criterion, id: &str) {
+        let client_id = ClientId::new("stand-alone", 0).unwrap();
+        let ctx = self.ctx(&client_id);
+        self.client
+            .verify_client_message(&ctx, &client_id, self.header.clone().into())
+            .unwrap();
+
+        c.benchmark_group("verify_client_message")
+            .throughput(Throughput::Elements(1))
+            .bench_function(id, |b| {
+                b.iter(|| {
+                    self.client
+                        .verify_client_message(&ctx, &client_id, self.header.clone().into())
+                        .unwrap()
+                })
+            });
+
+        c.benchmark_group("update_state")
+            .throughput(Throughput::Elements(1))
+            .bench_function(id, |b| {
+                // A fresh context every time, so the consensus state is always new.
+                b.iter_batched(
+                    || self.ctx(&client_id),
+                    |mut ctx| {
+                        self.client
+                            .update_state(&mut ctx, &client_id, self.header.clone().into())
+                            .unwrap()
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+    }
+}
+
+fn headers(c: &mut Criterion) {
+    Fixture::recorded().bench(c, "header.json");
+    for size in VALIDATOR_SET_SIZES {
+        Fixture::generated(size).bench(c, &format!("validators/{size}"));
+    }
+}
+
+/// Proof of the Osmosis packet commitment in `proof.json`, see `verify_membership_test`.
+fn membership(c: &mut Criterion) {
+    #[derive(Deserialize)]
+    struct ProofData {
+        proof_commitment: String,
+        data: String,
+        root: String,
+    }
+
+    let proof_data: ProofData = serde_json::from_str(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/data/proof.json"
+    )))
+    .unwrap();
+    let proof =
+        CommitmentProofBytes::try_from(base64_to_bytes(&proof_data.proof_commitment)).unwrap();
+    let root = CommitmentRoot::from_bytes(&base64_to_bytes(&proof_data.root));
+    let value = compute_packet_commitment(
+        &base64_to_bytes(&proof_data.data),
+        &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+        &Timestamp::from_nanoseconds(0).unwrap(),
+    )
+    .into_vec();
+    let path = Path::Commitment(CommitmentPath::new(
+        &PortId::transfer(),
+        &ChannelId::new(0),
+        Sequence::from(3514632),
+    ));
+    let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+    let client = client_state("chain2", Height::new(0, 6).unwrap());
+
+    c.benchmark_group("verify_membership")
+        .throughput(Throughput::Elements(1))
+        .bench_function("proof.json", |b| {
+            b.iter(|| {
+                client
+                    .verify_membership(&prefix, &proof, &root, path.clone(), value.clone())
+                    .unwrap()
+            })
+        });
+}
+
+criterion_group!(benches, headers, membership);
+criterion_main!(benches);
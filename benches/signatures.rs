@@ -0,0 +1,91 @@
+//! Compares `ProdVerifier` with `ParallelVerifier` on generated headers that
+//! skip one block, so the commit is checked against both validator sets.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ibc_core::client::types::Height;
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+    types::LightBlock as VerifierLightBlock, ProdVerifier, Verdict, Verifier,
+};
+use tendermint_lightclient::{client_options::ClientOptions, parallel::ParallelVerifier};
+use tendermint_testgen::{
+    light_block::TmLightBlock, Commit, Generator, Header, LightBlock, Validator,
+};
+
+fn light_block(validators: &[Validator], height: u64) -> VerifierLightBlock {
+    let header = Header::new(validators)
+        .chain_id("bench-0")
+        .height(height)
+        .time(Time::from_unix_timestamp(1_700_000_000 + height as i64, 0).unwrap());
+    let block: TmLightBlock = LightBlock::new(header.clone(), Commit::new(header, 1))
+        .generate()
+        .unwrap();
+    VerifierLightBlock::new(
+        block.signed_header,
+        block.validators,
+        block.next_validators,
+        block.provider,
+    )
+}
+
+fn verify(c: &mut Criterion) {
+    let options = ClientOptions::default()
+        .client_state(Height::new(0, 1).unwrap())
+        .unwrap()
+        .as_light_client_options()
+        .unwrap();
+    let now = Time::from_unix_timestamp(1_700_000_100, 0).unwrap();
+
+    let mut group = c.benchmark_group("verify_update_header");
+    for size in [10, 50, 150] {
+        let validators: Vec<Validator> = (0..size)
+            .map(|i| Validator::new(&format!("v{i}")).voting_power(10))
+            .collect();
+        let trusted = light_block(&validators, 1);
+        let untrusted = light_block(&validators, 3);
+
+        let prod = ProdVerifier::default();
+        let parallel = ParallelVerifier::default();
+        for verdict in [
+            prod.verify_update_header(
+                untrusted.as_untrusted_state(),
+                trusted.as_trusted_state(),
+                &options,
+                now,
+            ),
+            parallel.verify_update_header(
+                untrusted.as_untrusted_state(),
+                trusted.as_trusted_state(),
+                &options,
+                now,
+            ),
+        ] {
+            assert_eq!(verdict, Verdict::Success);
+        }
+
+        group.bench_with_input(BenchmarkId::new("prod", size), &size, |b, _| {
+            b.iter(|| {
+                prod.verify_update_header(
+                    untrusted.as_untrusted_state(),
+                    trusted.as_trusted_state(),
+                    &options,
+                    now,
+                )
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", size), &size, |b, _| {
+            b.iter(|| {
+                parallel.verify_update_header(
+                    untrusted.as_untrusted_state(),
+                    trusted.as_trusted_state(),
+                    &options,
+                    now,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+/*
+ * C ABI of the Tendermint light client, built with `--features ffi`.
+ *
+ * Every function but tmlc_client_free and tmlc_last_error returns TMLC_OK or
+ * one of the TMLC_ERR_* codes. On error, tmlc_last_error describes it.
+ *
+ * Memory ownership:
+ * - a client returned by tmlc_client_new is owned by the caller and freed
+ *   with tmlc_client_free, exactly once;
+ * - strings and buffers passed in are only read during the call;
+ * - the string returned by tmlc_last_error is owned by the library and valid
+ *   until the next call into the library on the same thread.
+ *
+ * A client must not be used by two threads at once, distinct clients may.
+ */
+
+#ifndef TENDERMINT_LIGHTCLIENT_H
+#define TENDERMINT_LIGHTCLIENT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TMLC_OK 0
+/* A required pointer argument is null. */
+#define TMLC_ERR_NULL_POINTER 1
+/* An argument could not be decoded: the config, a string, a header or a proof. */
+#define TMLC_ERR_INVALID_ARGUMENT 2
+/* The client rejected a header or a proof. */
+#define TMLC_ERR_VERIFICATION 3
+/* The library panicked, the client must not be used anymore. */
+#define TMLC_ERR_PANIC 4
+
+#define TMLC_STATUS_ACTIVE 0
+#define TMLC_STATUS_FROZEN 1
+#define TMLC_STATUS_EXPIRED 2
+#define TMLC_STATUS_UNAUTHORIZED 3
+
+typedef struct TmlcClient TmlcClient;
+
+typedef struct {
+    uint64_t revision_number;
+    uint64_t revision_height;
+} TmlcHeight;
+
+typedef struct {
+    /* One of the TMLC_STATUS_* values. */
+    int32_t status;
+    TmlcHeight latest_height;
+} TmlcStatus;
+
+/*
+ * Creates a client from a JSON config and stores it in *out:
+ *
+ *   {
+ *     "chain_id": "osmosis-1",
+ *     "trusted_height": "1-5200000",
+ *     "consensus_state": { "root": "...", "timestamp": "...", "next_validators_hash": "..." },
+ *     "trusting_period": "14d"
+ *   }
+ *
+ * consensus_state has the format of the CLI consensus state files.
+ * trust_threshold, trusting_period, unbonding_period and max_clock_drift are
+ * optional and take the values and defaults of the CLI flags. host_time, an
+ * RFC 3339 time headers are evaluated at, defaults to now.
+ */
+int32_t tmlc_client_new(const char *config, TmlcClient **out);
+
+/* Frees a client created by tmlc_client_new. Null is ignored. */
+void tmlc_client_free(TmlcClient *client);
+
+/*
+ * Verifies a protobuf encoded ibc.lightclients.tendermint.v1.Header and
+ * installs its consensus state. Its height is stored in *out_height unless it
+ * is null.
+ */
+int32_t tmlc_client_update(TmlcClient *client, const uint8_t *header, size_t header_len,
+                           TmlcHeight *out_height);
+
+/*
+ * Verifies that value is stored at path, e.g.
+ * "commitments/ports/transfer/channels/channel-0/sequences/1", under prefix,
+ * e.g. "ibc", in the state committed by the consensus state at height. proof
+ * is a protobuf encoded ibc.core.commitment.v1.MerkleProof.
+ */
+int32_t tmlc_client_verify_membership(const TmlcClient *client, TmlcHeight height,
+                                      const char *prefix, const char *path,
+                                      const uint8_t *proof, size_t proof_len,
+                                      const uint8_t *value, size_t value_len);
+
+/*
+ * Verifies that nothing is stored at path under prefix in the state committed
+ * by the consensus state at height.
+ */
+int32_t tmlc_client_verify_non_membership(const TmlcClient *client, TmlcHeight height,
+                                          const char *prefix, const char *path,
+                                          const uint8_t *proof, size_t proof_len);
+
+/* Stores the status and latest height of the client in *out. */
+int32_t tmlc_client_status(const TmlcClient *client, TmlcStatus *out);
+
+/* Message of the last error on this thread, null if the last call succeeded. */
+const char *tmlc_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TENDERMINT_LIGHTCLIENT_H */
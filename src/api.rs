@@ -1,10 +1,11 @@
 use ibc_client_tendermint::{client_state::ClientState, consensus_state::ConsensusState};
 
-use crate::context::ClientType;
+use crate::{context::ClientType, storage::Storage};
 
 pub struct TendermintClient;
 
 impl ClientType for TendermintClient {
     type ClientState = ClientState;
     type ConsensusState = ConsensusState;
+    type Store = Storage<Self>;
 }
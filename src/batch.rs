@@ -0,0 +1,329 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+    time::Instant,
+};
+
+use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState, Header};
+use ibc_core::{client::types::Height, host::types::identifiers::ClientId};
+
+use tendermint::Time;
+
+use crate::{
+    api::TendermintClient, client_message::check_chain_id, context::Ctx, light_client::LightClient,
+    metrics::Metrics, parallel::ParallelVerifier,
+};
+
+/// Reads headers from a directory of JSON files, one header per file, or from
+/// a file, `-` for stdin, with one JSON header per line.
+pub fn read_headers(path: &str) -> Result<Vec<Header>, Box<dyn Error>> {
+    if Path::new(path).is_dir() {
+        let mut files: Vec<_> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        files.retain(|file| file.extension().is_some_and(|ext| ext == "json"));
+        files.sort();
+
+        return files
+            .iter()
+            .map(|file| {
+                serde_json::from_str(&fs::read_to_string(file)?)
+                    .map_err(|e| format!("{} is not a header: {e}", file.display()).into())
+            })
+            .collect();
+    }
+
+    let content = if path == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        fs::read_to_string(path)?
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1).into())
+        })
+        .collect()
+}
+
+/// Client that applies headers one after another, so each header can trust
+/// any consensus state installed by the previous ones.
+pub struct BatchVerifier {
+    client: LightClient,
+    parallel: bool,
+    metrics: Option<Metrics>,
+}
+
+impl BatchVerifier {
+    /// Client trusting `cs` at `client_state.latest_height`, evaluating
+    /// headers as of `host_time`, or now if it is `None`.
+    pub fn new(
+        client_state: ClientStateType,
+        cs: ConsensusState,
+        host_time: Option<Time>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let client_id = ClientId::new("stand-alone", 0)?;
+        let ctx = Ctx::default().with_host_time(host_time);
+        Ok(Self {
+            client: LightClient::initialise(ctx, client_id, client_state, cs)?,
+            parallel: false,
+            metrics: None,
+        })
+    }
+
+    /// Client the headers are applied to.
+    pub fn client(&self) -> &LightClient {
+        &self.client
+    }
+
+    /// Identifier the client is stored under in [`Self::ctx`].
+    pub fn client_id(&self) -> &ClientId {
+        self.client.client_id()
+    }
+
+    /// Context holding the client and every consensus state installed so far.
+    pub fn ctx(&self) -> &Ctx<TendermintClient> {
+        self.client.ctx()
+    }
+
+    /// Checks the commit signatures of every header in parallel, see [`ParallelVerifier`].
+    pub fn with_parallel_signatures(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Records verification latency, failures and the trusted height in `metrics`.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        metrics.observe_client(&self);
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Verifies `header` and installs its consensus state.
+    pub fn apply(&mut self, header: &Header) -> Result<(), Box<dyn Error>> {
+        let start = Instant::now();
+        let result = self.verify_and_update(header);
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_verification(start.elapsed(), result.as_ref().err().map(|e| &**e));
+            metrics.observe_client(self);
+        }
+        result
+    }
+
+    fn verify_and_update(&mut self, header: &Header) -> Result<(), Box<dyn Error>> {
+        check_chain_id(header, &self.client.client_state()?.inner().chain_id)?;
+        if self.parallel {
+            self.client
+                .update_with_verifier(header.clone(), &ParallelVerifier::default())?;
+        } else {
+            self.client.update(header.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of one header of a batch.
+pub struct HeaderResult {
+    pub height: Height,
+    pub error: Option<String>,
+}
+
+impl fmt::Display for HeaderResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            None => write!(f, "{}: valid", self.height),
+            Some(e) => write!(f, "{}: invalid: {e}", self.height),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BatchOptions {
+    /// Verify the remaining headers after an invalid one.
+    pub keep_going: bool,
+    /// Check commit signatures in parallel.
+    pub parallel: bool,
+    /// Time the headers are evaluated at, now if unset.
+    pub at: Option<Time>,
+}
+
+/// Verifies `headers` in height order with a client trusting `cs`. The trusted
+/// height of the client is taken from the lowest header, every header must be
+/// from the chain of `client_state`.
+pub fn verify_batch(
+    client_state: ClientStateType,
+    cs: ConsensusState,
+    mut headers: Vec<Header>,
+    options: BatchOptions,
+) -> Result<Vec<HeaderResult>, Box<dyn Error>> {
+    headers.sort_by_key(|header| header.height());
+    let first = headers.first().ok_or("no headers to verify")?;
+
+    let mut client_state = client_state;
+    client_state.latest_height = first.trusted_height;
+    let mut verifier = BatchVerifier::new(client_state, cs, options.at)?
+        .with_parallel_signatures(options.parallel);
+
+    let mut results = Vec::with_capacity(headers.len());
+    for header in &headers {
+        let error = verifier.apply(header).err().map(|e| e.to_string());
+        let failed = error.is_some();
+        results.push(HeaderResult {
+            height: header.height(),
+            error,
+        });
+        if failed && !options.keep_going {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use tendermint::Time;
+
+    use crate::{
+        test_utils::{chain_client_state, client_state, temp_path},
+        testgen::{ChainSpec, Corruption, TestChain},
+    };
+
+    fn chain() -> TestChain {
+        ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap()
+    }
+
+    #[test]
+    fn applies_headers_in_height_order() {
+        let chain = chain();
+        // out of order, each header trusts its predecessor
+        let headers = [4, 2, 3, 5]
+            .iter()
+            .map(|h| chain.header(*h, h - 1).unwrap())
+            .collect();
+
+        let results = verify_batch(
+            chain_client_state(&chain),
+            chain.consensus_state(1).unwrap(),
+            headers,
+            BatchOptions::default(),
+        )
+        .unwrap();
+        let heights: Vec<u64> = results.iter().map(|r| r.height.revision_height()).collect();
+        assert_eq!(heights, vec![2, 3, 4, 5]);
+        assert!(results.iter().all(|r| r.error.is_none()));
+    }
+
+    #[test]
+    fn stops_or_continues_on_failure() {
+        let chain = chain();
+        let headers = vec![
+            chain.header(2, 1).unwrap(),
+            chain.header(3, 2).unwrap(),
+            chain
+                .corrupted_header(4, 3, Corruption::BadSignature)
+                .unwrap(),
+            chain.header(5, 3).unwrap(),
+        ];
+        let cs = chain.consensus_state(1).unwrap();
+
+        let stopped = verify_batch(
+            chain_client_state(&chain),
+            cs.clone(),
+            headers.clone(),
+            BatchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(stopped.len(), 3);
+        assert!(stopped[2].error.is_some());
+
+        for parallel in [false, true] {
+            let options = BatchOptions {
+                keep_going: true,
+                parallel,
+                ..BatchOptions::default()
+            };
+            let all = verify_batch(
+                chain_client_state(&chain),
+                cs.clone(),
+                headers.clone(),
+                options,
+            )
+            .unwrap();
+            assert_eq!(all.len(), 4);
+            let error = all[2].error.as_ref().unwrap();
+            assert!(error.contains("failed to verify signature"), "{error}");
+            assert!(all[3].error.is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_headers_of_other_chain() {
+        let chain = chain();
+        let results = verify_batch(
+            client_state(),
+            chain.consensus_state(1).unwrap(),
+            vec![chain.header(2, 1).unwrap()],
+            BatchOptions::default(),
+        )
+        .unwrap();
+        let error = results[0].error.as_ref().unwrap();
+        assert!(error.contains("is from chain `testgen-0`"), "{error}");
+    }
+
+    #[test]
+    fn frozen_client_rejects_parallel_updates() {
+        let chain = chain();
+        // the same validators sign another block 3
+        let fork = ChainSpec {
+            start_time: (chain.spec().start_time + Duration::from_secs(1)).unwrap(),
+            ..chain.spec().clone()
+        }
+        .generate()
+        .unwrap();
+        let client_state = ClientStateType {
+            latest_height: chain.ibc_height(1).unwrap(),
+            ..chain_client_state(&chain)
+        };
+        let mut verifier =
+            BatchVerifier::new(client_state, chain.consensus_state(1).unwrap(), None)
+                .unwrap()
+                .with_parallel_signatures(true);
+
+        verifier.apply(&chain.header(3, 1).unwrap()).unwrap();
+        let err = verifier.apply(&fork.header(3, 1).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("frozen"), "{err}");
+
+        let err = verifier.apply(&chain.header(4, 3).unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "client is not active. Status=`Frozen`");
+    }
+
+    #[test]
+    fn reads_ndjson_headers() {
+        let chain = chain();
+        let path = temp_path("headers.ndjson");
+        let lines: Vec<String> = (2..5)
+            .map(|h| serde_json::to_string(&chain.header(h, h - 1).unwrap()).unwrap())
+            .collect();
+        fs::write(&path, lines.join("\n") + "\n\n").unwrap();
+
+        let headers = read_headers(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers[0], chain.header(2, 1).unwrap());
+    }
+}
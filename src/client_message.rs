@@ -0,0 +1,325 @@
+use std::error::Error;
+
+use base64::Engine;
+use ibc_client_tendermint::{
+    client_state::ClientState,
+    consensus_state::ConsensusState as TmConsensusState,
+    types::{
+        ClientState as ClientStateType, ConsensusState, Header, Misbehaviour,
+        TENDERMINT_HEADER_TYPE_URL, TENDERMINT_MISBEHAVIOUR_TYPE_URL,
+    },
+};
+use ibc_core::{
+    client::{
+        context::{
+            client_state::{ClientStateExecution, ClientStateValidation},
+            ClientExecutionContext,
+        },
+        types::{
+            msgs::{MsgUpdateClient, UPDATE_CLIENT_TYPE_URL},
+            Height,
+        },
+    },
+    host::types::{
+        identifiers::{ChainId, ClientId},
+        path::ClientConsensusStatePath,
+    },
+    primitives::proto::{Any, Protobuf},
+};
+use ibc_proto::cosmos::tx::v1beta1::Tx;
+use prost::Message;
+use tendermint::Time;
+
+use crate::{api::TendermintClient, context::Ctx};
+
+/// Tendermint client message carried by a `MsgUpdateClient`.
+#[derive(Clone, Debug)]
+pub enum TmClientMessage {
+    Header(Box<Header>),
+    Misbehaviour(Box<Misbehaviour>),
+}
+
+impl TryFrom<Any> for TmClientMessage {
+    type Error = Box<dyn Error>;
+
+    fn try_from(any: Any) -> Result<Self, Self::Error> {
+        match any.type_url.as_str() {
+            TENDERMINT_HEADER_TYPE_URL => Header::try_from(any)
+                .map(|header| Self::Header(Box::new(header)))
+                .map_err(|e| format!("invalid Tendermint header: {e}").into()),
+            TENDERMINT_MISBEHAVIOUR_TYPE_URL => Misbehaviour::try_from(any)
+                .map(|misbehaviour| Self::Misbehaviour(Box::new(misbehaviour)))
+                .map_err(|e| format!("invalid Tendermint misbehaviour: {e}").into()),
+            other => Err(format!("unsupported client message type `{other}`").into()),
+        }
+    }
+}
+
+impl From<TmClientMessage> for Any {
+    fn from(message: TmClientMessage) -> Self {
+        match message {
+            TmClientMessage::Header(header) => (*header).into(),
+            TmClientMessage::Misbehaviour(misbehaviour) => (*misbehaviour).into(),
+        }
+    }
+}
+
+impl TmClientMessage {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Header(_) => "header",
+            Self::Misbehaviour(_) => "misbehaviour",
+        }
+    }
+
+    fn headers(&self) -> Vec<&Header> {
+        match self {
+            Self::Header(header) => vec![header],
+            Self::Misbehaviour(misbehaviour) => {
+                vec![misbehaviour.header1(), misbehaviour.header2()]
+            }
+        }
+    }
+
+    /// Heights of the headers in the message.
+    pub fn heights(&self) -> Vec<Height> {
+        self.headers().iter().map(|h| h.height()).collect()
+    }
+
+    /// Heights the message expects the client to already trust, without duplicates.
+    pub fn trusted_heights(&self) -> Vec<Height> {
+        let mut heights: Vec<Height> = self.headers().iter().map(|h| h.trusted_height).collect();
+        heights.dedup();
+        heights
+    }
+
+    /// Checks that every header in the message is from `chain_id`.
+    pub fn check_chain_id(&self, chain_id: &ChainId) -> Result<(), Box<dyn Error>> {
+        self.headers()
+            .into_iter()
+            .try_for_each(|header| check_chain_id(header, chain_id))
+    }
+}
+
+/// Checks that `header` is from `chain_id`, the chain the client was
+/// configured with. Headers carry the chain id they claim, it is never
+/// trusted on its own.
+pub fn check_chain_id(header: &Header, chain_id: &ChainId) -> Result<(), Box<dyn Error>> {
+    let header_chain_id = header.signed_header.header.chain_id.as_str();
+    if header_chain_id != chain_id.as_str() {
+        return Err(format!(
+            "header at {} is from chain `{header_chain_id}`, the client tracks `{chain_id}`",
+            header.height()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Decodes a Cosmos SDK transaction. `input` can be:
+///  - the JSON returned by the Tendermint RPC `/tx` endpoint (`result.tx` or `tx` field),
+///  - the transaction proto bytes encoded as hex or base64,
+///  - the raw transaction proto bytes.
+pub fn decode_tx(input: &[u8]) -> Result<Tx, Box<dyn Error>> {
+    let text = input.trim_ascii();
+    let bytes = if text.starts_with(b"{") {
+        let json: serde_json::Value = serde_json::from_slice(text)?;
+        let tx = json
+            .pointer("/result/tx")
+            .or_else(|| json.get("tx"))
+            .and_then(serde_json::Value::as_str)
+            .ok_or("JSON input has no `result.tx` or `tx` field")?;
+        base64::engine::general_purpose::STANDARD.decode(tx)?
+    } else if let Ok(text) = std::str::from_utf8(text) {
+        hex::decode(text)
+            .or_else(|_| base64::engine::general_purpose::STANDARD.decode(text))
+            .unwrap_or_else(|_| input.to_vec())
+    } else {
+        input.to_vec()
+    };
+
+    Tx::decode(bytes.as_slice()).map_err(|e| format!("invalid transaction: {e}").into())
+}
+
+/// Extracts every `MsgUpdateClient` from a transaction.
+pub fn update_client_messages(tx: &Tx) -> Result<Vec<MsgUpdateClient>, Box<dyn Error>> {
+    let body = tx.body.as_ref().ok_or("transaction has no body")?;
+    let msgs = body
+        .messages
+        .iter()
+        .filter(|msg| msg.type_url == UPDATE_CLIENT_TYPE_URL)
+        .map(|msg| {
+            MsgUpdateClient::decode_vec(&msg.value)
+                .map_err(|e| format!("invalid MsgUpdateClient: {e}").into())
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+    if msgs.is_empty() {
+        return Err("transaction contains no MsgUpdateClient".into());
+    }
+    Ok(msgs)
+}
+
+/// Runs a client message through [`Ctx`] on top of the given trusted consensus
+/// states, one per height in [`TmClientMessage::trusted_heights`].
+///
+/// The message must be from the chain of `client_state`, whose latest height
+/// is taken from the message. The message is evaluated as of `host_time`,
+/// or now if it is `None`. Returns whether the message is evidence of
+/// misbehaviour.
+pub fn verify_client_message(
+    client_state: ClientStateType,
+    client_id: &ClientId,
+    message: &TmClientMessage,
+    trusted: &[ConsensusState],
+    host_time: Option<Time>,
+) -> Result<bool, Box<dyn Error>> {
+    let trusted_heights = message.trusted_heights();
+    if trusted.len() < trusted_heights.len() {
+        return Err(format!(
+            "{} trusts {} heights but only {} consensus states were given",
+            message.kind(),
+            trusted_heights.len(),
+            trusted.len()
+        )
+        .into());
+    }
+
+    message.check_chain_id(&client_state.chain_id)?;
+
+    let mut client_state = client_state;
+    client_state.latest_height = trusted_heights[0];
+    let client = ClientState::from(client_state);
+
+    let mut ctx: Ctx<TendermintClient> = Ctx::default().with_host_time(host_time);
+    client.initialise(&mut ctx, client_id, trusted[0].clone().into())?;
+    for (height, cs) in trusted_heights.iter().zip(trusted).skip(1) {
+        ctx.store_consensus_state(
+            ClientConsensusStatePath::new(
+                client_id.clone(),
+                height.revision_number(),
+                height.revision_height(),
+            ),
+            TmConsensusState::from(cs.clone()),
+        )?;
+    }
+
+    let any: Any = message.clone().into();
+    client
+        .verify_client_message(&ctx, client_id, any.clone())
+        .map_err(|e| format!("{} verification failed: {e}", message.kind()))?;
+
+    let misbehaviour = client
+        .check_for_misbehaviour(&ctx, client_id, any)
+        .map_err(|e| format!("misbehaviour check failed: {e}"))?;
+
+    Ok(misbehaviour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_core::primitives::Signer;
+    use ibc_proto::cosmos::tx::v1beta1::TxBody;
+
+    use crate::test_utils::{client_state, dummy_consensus_state, get_header};
+
+    fn update_client_tx(client_message: Any) -> Tx {
+        let msg = MsgUpdateClient {
+            client_id: ClientId::new("07-tendermint", 0).unwrap(),
+            client_message,
+            signer: Signer::from("cosmos1relayer".to_string()),
+        };
+        Tx {
+            body: Some(TxBody {
+                messages: vec![Any {
+                    type_url: UPDATE_CLIENT_TYPE_URL.to_string(),
+                    value: msg.encode_vec(),
+                }],
+                ..Default::default()
+            }),
+            auth_info: None,
+            signatures: vec![],
+        }
+    }
+
+    #[test]
+    fn verify_update_client_tx() {
+        let tx_bytes = update_client_tx(get_header().into()).encode_to_vec();
+
+        let rpc_json = format!(
+            r#"{{"jsonrpc":"2.0","id":-1,"result":{{"tx":"{}"}}}}"#,
+            base64::engine::general_purpose::STANDARD.encode(&tx_bytes)
+        );
+
+        for input in [
+            tx_bytes.clone(),
+            hex::encode(&tx_bytes).into_bytes(),
+            rpc_json.into_bytes(),
+        ] {
+            let tx = decode_tx(&input).expect("decode tx");
+            let msgs = update_client_messages(&tx).unwrap();
+            assert_eq!(msgs.len(), 1);
+
+            let message = TmClientMessage::try_from(msgs[0].client_message.clone()).unwrap();
+            assert_eq!(message.trusted_heights(), vec![Height::new(0, 6).unwrap()]);
+
+            let misbehaviour = verify_client_message(
+                client_state(),
+                &msgs[0].client_id,
+                &message,
+                &[dummy_consensus_state()],
+                None,
+            )
+            .expect("valid header");
+            assert!(!misbehaviour);
+        }
+    }
+
+    #[test]
+    fn reports_failed_check() {
+        let mut header = get_header();
+        header.signed_header.commit.signatures.truncate(0);
+        let tx = update_client_tx(header.into());
+
+        let msgs = update_client_messages(&tx).unwrap();
+        let message = TmClientMessage::try_from(msgs[0].client_message.clone()).unwrap();
+        let err = verify_client_message(
+            client_state(),
+            &msgs[0].client_id,
+            &message,
+            &[dummy_consensus_state()],
+            None,
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().starts_with("header verification failed"));
+        assert!(err.to_string().contains("no signatures for commit"));
+    }
+
+    #[test]
+    fn rejects_header_of_other_chain() {
+        let message = TmClientMessage::Header(Box::new(get_header()));
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new("ibc-0").unwrap();
+
+        let err = verify_client_message(
+            client_state,
+            &ClientId::new("07-tendermint", 0).unwrap(),
+            &message,
+            &[dummy_consensus_state()],
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("is from chain `chain2`"), "{err}");
+    }
+
+    #[test]
+    fn rejects_tx_without_update_client() {
+        let mut tx = update_client_tx(get_header().into());
+        tx.body.as_mut().unwrap().messages.clear();
+
+        assert!(update_client_messages(&tx).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::{error::Error, time::Duration};
+
+use clap::{Args, Parser};
+use ibc_client_tendermint::types::{AllowUpdate, ClientState as ClientStateType, TrustThreshold};
+use ibc_core::{
+    client::types::Height, commitment_types::specs::ProofSpecs, host::types::identifiers::ChainId,
+};
+
+/// Parameters of the client every command builds.
+#[derive(Args, Clone, Debug)]
+pub struct ClientOptions {
+    /// Chain the client tracks, headers of any other chain are rejected.
+    #[arg(long, global = true, default_value = "ibc-0")]
+    pub chain_id: ChainId,
+    /// Fraction of the trusted validator set's voting power that must sign a
+    /// non adjacent header, between 1/3 and 1 excluded.
+    #[arg(long, global = true, default_value = "1/3", value_parser = parse_trust_threshold)]
+    pub trust_threshold: TrustThreshold,
+    /// How long a consensus state is trusted, e.g. `14d`, `36h` or `600s`.
+    #[arg(long, global = true, default_value = "1825d", value_parser = parse_duration)]
+    pub trusting_period: Duration,
+    /// Unbonding period of the chain, longer than the trusting period.
+    #[arg(long, global = true, default_value = "1826d", value_parser = parse_duration)]
+    pub unbonding_period: Duration,
+    /// Largest clock difference allowed between the chain and this host.
+    #[arg(long, global = true, default_value = "40s", value_parser = parse_duration)]
+    pub max_clock_drift: Duration,
+}
+
+impl Default for ClientOptions {
+    /// The defaults of the flags.
+    fn default() -> Self {
+        #[derive(Parser)]
+        struct Defaults {
+            #[command(flatten)]
+            client: ClientOptions,
+        }
+
+        Defaults::parse_from(["defaults"]).client
+    }
+}
+
+impl ClientOptions {
+    /// Client state with these parameters. `ClientStateType::new` checks them.
+    pub fn client_state(&self, latest_height: Height) -> Result<ClientStateType, Box<dyn Error>> {
+        Ok(ClientStateType::new(
+            self.chain_id.clone(),
+            self.trust_threshold,
+            self.trusting_period,
+            self.unbonding_period,
+            self.max_clock_drift,
+            latest_height,
+            ProofSpecs::cosmos(),
+            vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+            AllowUpdate {
+                after_expiry: true,
+                after_misbehaviour: true,
+            },
+        )?)
+    }
+}
+
+/// Parses a trust threshold written as `numerator/denominator`, e.g. `2/3`.
+pub fn parse_trust_threshold(s: &str) -> Result<TrustThreshold, String> {
+    let (numerator, denominator) = s
+        .split_once('/')
+        .ok_or_else(|| format!("expected a fraction like 2/3, got `{s}`"))?;
+    let numerator = numerator.trim().parse().map_err(|e| format!("{e}"))?;
+    let denominator = denominator.trim().parse().map_err(|e| format!("{e}"))?;
+    TrustThreshold::new(numerator, denominator).map_err(|e| e.to_string())
+}
+
+/// Parses a duration written as a number followed by `s`, `m`, `h` or `d`.
+/// A number alone is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (value, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("expected a duration like 14d, got `{s}`"))?;
+    let secs = match unit {
+        "s" => Some(value),
+        "m" => value.checked_mul(60),
+        "h" => value.checked_mul(60 * 60),
+        "d" => value.checked_mul(24 * 60 * 60),
+        _ => {
+            return Err(format!(
+                "unknown duration unit `{unit}`, expected s, m, h or d"
+            ))
+        }
+    };
+    secs.map(Duration::from_secs)
+        .ok_or_else(|| format!("duration `{s}` is too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_client_tendermint::client_state::ClientState;
+    use ibc_core::{
+        client::context::client_state::{ClientStateExecution, ClientStateValidation},
+        host::types::identifiers::ClientId,
+    };
+    use tendermint::Time;
+
+    use crate::{
+        api::TendermintClient,
+        context::Ctx,
+        testgen::{ChainSpec, KeyType, ValidatorChange, ValidatorSpec},
+    };
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        client: ClientOptions,
+    }
+
+    fn options(args: &[&str]) -> ClientOptions {
+        Cli::try_parse_from([&["cli"], args].concat())
+            .unwrap()
+            .client
+    }
+
+    #[test]
+    fn parses_flags() {
+        let client = options(&[
+            "--chain-id",
+            "osmosis-1",
+            "--trust-threshold",
+            "2/3",
+            "--trusting-period",
+            "14d",
+            "--unbonding-period",
+            "21d",
+            "--max-clock-drift",
+            "10",
+        ]);
+        assert_eq!(client.trust_threshold, TrustThreshold::TWO_THIRDS);
+        assert_eq!(
+            client.trusting_period,
+            Duration::from_secs(14 * 24 * 60 * 60)
+        );
+        assert_eq!(client.max_clock_drift, Duration::from_secs(10));
+
+        let client_state = client.client_state(Height::new(1, 1).unwrap()).unwrap();
+        assert_eq!(client_state.chain_id, ChainId::new("osmosis-1").unwrap());
+        assert_eq!(client_state.trust_level, TrustThreshold::TWO_THIRDS);
+    }
+
+    #[test]
+    fn defaults_match_flags() {
+        let client = ClientOptions::default();
+        assert_eq!(client.chain_id, ChainId::new("ibc-0").unwrap());
+        assert_eq!(client.trust_threshold, TrustThreshold::ONE_THIRD);
+        assert_eq!(
+            client.trusting_period,
+            Duration::from_secs(1825 * 24 * 60 * 60)
+        );
+        assert_eq!(client.max_clock_drift, Duration::from_secs(40));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for threshold in ["2", "3/2", "1/1", "a/3"] {
+            assert!(parse_trust_threshold(threshold).is_err(), "{threshold}");
+        }
+        for duration in ["", "14w", "-1s", "99999999999999999999d"] {
+            assert!(parse_duration(duration).is_err(), "{duration}");
+        }
+
+        // values that parse but break the client state invariants
+        let height = Height::new(0, 1).unwrap();
+        for args in [
+            &["--trust-threshold", "1/4"][..],
+            &["--trust-threshold", "0/0"],
+            &["--trusting-period", "21d", "--unbonding-period", "14d"],
+            &["--trusting-period", "0s"],
+        ] {
+            assert!(options(args).client_state(height).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn stricter_threshold_rejects_header() {
+        // half of the trusted voting power signs the last header
+        let spec = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            length: 3,
+            changes: vec![ValidatorChange {
+                height: 3,
+                validators: ["a", "b", "x", "y"]
+                    .iter()
+                    .map(|id| ValidatorSpec {
+                        id: id.to_string(),
+                        power: 10,
+                        key_type: KeyType::Ed25519,
+                    })
+                    .collect(),
+            }],
+            ..ChainSpec::default()
+        };
+        let chain = spec.generate().unwrap();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+
+        for (threshold, valid) in [("1/3", true), ("2/3", false)] {
+            let client_state =
+                options(&["--chain-id", &spec.chain_id, "--trust-threshold", threshold])
+                    .client_state(chain.ibc_height(1).unwrap())
+                    .unwrap();
+            let client = ClientState::from(client_state);
+            let mut ctx: Ctx<TendermintClient> = Ctx::default();
+            client
+                .initialise(
+                    &mut ctx,
+                    &client_id,
+                    chain.consensus_state(1).unwrap().into(),
+                )
+                .unwrap();
+
+            let result =
+                client.verify_client_message(&ctx, &client_id, chain.header(3, 1).unwrap().into());
+            assert_eq!(result.is_ok(), valid, "{threshold}: {result:?}");
+        }
+    }
+}
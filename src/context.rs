@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use ibc_core::client::context::consensus_state::ConsensusState as ConsensusStateTrait;
 use ibc_core::client::context::ExtClientValidationContext;
 
@@ -7,32 +9,76 @@ use ibc_core::handler::types::error::ContextError;
 use ibc_core::{
     client::{
         context::{
-            client_state::{ClientStateCommon, ClientStateExecution},
-            ClientExecutionContext, ClientValidationContext,
+            client_state::ClientStateExecution, ClientExecutionContext, ClientValidationContext,
         },
         types::Height,
     },
     host::types::identifiers::ClientId,
 };
 use tendermint::Time;
+use tracing::instrument;
 
-use crate::storage::{Direction, Storage};
+use crate::storage::{ClientStore, Direction};
 
 pub struct Ctx<C: ClientType> {
-    storage: Storage<C>,
+    storage: C::Store,
+    /// Time client messages are evaluated at, the wall clock if unset.
+    host_time: Option<Time>,
+    /// Height update metadata is recorded at, the store's current height or
+    /// `0-1` if unset.
+    host_height: Option<Height>,
 }
 
-impl<C: ClientType> Default for Ctx<C> {
+impl<C: ClientType> Default for Ctx<C>
+where
+    C::Store: Default,
+{
     fn default() -> Self {
+        Self::new(C::Store::default())
+    }
+}
+
+impl<C: ClientType> Ctx<C> {
+    /// Context over `storage`, which may already hold a client.
+    pub fn new(storage: C::Store) -> Self {
         Self {
-            storage: Storage::default(),
+            storage,
+            host_time: None,
+            host_height: None,
         }
     }
+
+    /// Evaluates client messages as of `time` instead of now, so historical
+    /// headers can be verified with the real trusting period.
+    pub fn with_host_time(mut self, time: Option<Time>) -> Self {
+        self.host_time = time;
+        self
+    }
+
+    /// Height of the host chain, for hosts that enforce block delay periods.
+    pub fn with_host_height(mut self, height: Option<Height>) -> Self {
+        self.host_height = height;
+        self
+    }
+
+    pub fn storage(&self) -> &C::Store {
+        &self.storage
+    }
+
+    pub fn into_storage(self) -> C::Store {
+        self.storage
+    }
 }
 
 pub trait ClientType: Sized {
     type ClientState: ClientStateExecution<Ctx<Self>> + Clone;
     type ConsensusState: ConsensusStateTrait + Clone;
+    /// Where the client and its consensus states are kept.
+    type Store: ClientStore<Self>;
+}
+
+fn height(revision_number: u64, revision_height: u64) -> Result<Height, ContextError> {
+    Ok(Height::new(revision_number, revision_height)?)
 }
 
 impl<C: ClientType> ClientValidationContext for Ctx<C> {
@@ -40,40 +86,52 @@ impl<C: ClientType> ClientValidationContext for Ctx<C> {
 
     type ConsensusStateRef = C::ConsensusState;
 
-    fn client_state(&self, _client_id: &ClientId) -> Result<Self::ClientStateRef, ContextError> {
-        Ok(self.storage.client_state.clone().unwrap())
+    #[instrument(level = "trace", skip_all, fields(%client_id))]
+    fn client_state(&self, client_id: &ClientId) -> Result<Self::ClientStateRef, ContextError> {
+        self.storage.client_state().ok_or_else(|| {
+            ClientError::ClientStateNotFound {
+                client_id: client_id.clone(),
+            }
+            .into()
+        })
     }
 
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %client_cons_state_path),
+        err(level = "debug")
+    )]
     fn consensus_state(
         &self,
         client_cons_state_path: &ibc_core::host::types::path::ClientConsensusStatePath,
     ) -> Result<Self::ConsensusStateRef, ContextError> {
-        let cons_state = self
-            .storage
-            .consensus_state
-            .get(&client_cons_state_path.leaf());
-        match cons_state {
-            Some(state) => Ok(state.to_owned()),
-            None => Err(ContextError::ClientError(
-                ibc_core::client::types::error::ClientError::ConsensusStateNotFound {
-                    client_id: client_cons_state_path.clone().client_id,
-                    height: Height::new(
-                        client_cons_state_path.revision_number,
-                        client_cons_state_path.revision_height,
-                    )
-                    .unwrap(),
-                },
-            )),
-        }
+        let height = height(
+            client_cons_state_path.revision_number,
+            client_cons_state_path.revision_height,
+        )?;
+        self.storage.consensus_state(&height).ok_or_else(|| {
+            ClientError::ConsensusStateNotFound {
+                client_id: client_cons_state_path.client_id.clone(),
+                height,
+            }
+            .into()
+        })
     }
 
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(%client_id, %height),
+        err(level = "debug")
+    )]
     fn client_update_meta(
         &self,
         client_id: &ibc_core::host::types::identifiers::ClientId,
         height: &ibc_core::client::types::Height,
     ) -> Result<(ibc_core::primitives::Timestamp, Height), ContextError> {
-        match self.storage.update_meta.get(height) {
-            Some(meta) => Ok(meta.to_owned()),
+        match self.storage.update_meta(height) {
+            Some(meta) => Ok(meta),
             None => Err(ClientError::UpdateMetaDataNotFound {
                 client_id: client_id.clone(),
                 height: *height,
@@ -95,36 +153,44 @@ impl<C: ClientType> ClientExecutionContext for Ctx<C> {
 
     type ClientStateMut = C::ClientState;
 
+    #[instrument(level = "trace", skip_all, fields(path = %_client_state_path))]
     fn store_client_state(
         &mut self,
         _client_state_path: ibc_core::host::types::path::ClientStatePath,
         client_state: Self::ClientStateRef,
     ) -> Result<(), ContextError> {
-        self.storage.client_state = Some(client_state);
+        self.storage.set_client_state(client_state);
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, fields(path = %consensus_state_path))]
     fn store_consensus_state(
         &mut self,
         consensus_state_path: ibc_core::host::types::path::ClientConsensusStatePath,
         consensus_state: Self::ConsensusStateRef,
     ) -> Result<(), ContextError> {
-        self.storage
-            .consensus_state
-            .insert(consensus_state_path.leaf(), consensus_state);
+        let height = height(
+            consensus_state_path.revision_number,
+            consensus_state_path.revision_height,
+        )?;
+        self.storage.set_consensus_state(height, consensus_state);
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, fields(path = %consensus_state_path))]
     fn delete_consensus_state(
         &mut self,
         consensus_state_path: ibc_core::host::types::path::ClientConsensusStatePath,
     ) -> Result<(), ContextError> {
-        self.storage
-            .consensus_state
-            .remove(&consensus_state_path.leaf());
+        let height = height(
+            consensus_state_path.revision_number,
+            consensus_state_path.revision_height,
+        )?;
+        self.storage.remove_consensus_state(&height);
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, fields(%height, %host_timestamp, %host_height))]
     fn store_update_meta(
         &mut self,
         _client_id: ibc_core::host::types::identifiers::ClientId,
@@ -133,33 +199,42 @@ impl<C: ClientType> ClientExecutionContext for Ctx<C> {
         host_height: Height,
     ) -> Result<(), ContextError> {
         self.storage
-            .update_meta
-            .insert(height, (host_timestamp, host_height));
+            .set_update_meta(height, (host_timestamp, host_height));
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, fields(%height))]
     fn delete_update_meta(
         &mut self,
         _client_id: ibc_core::host::types::identifiers::ClientId,
         height: Height,
     ) -> Result<(), ContextError> {
-        self.storage.update_meta.remove(&height);
+        self.storage.remove_update_meta(&height);
         Ok(())
     }
 }
 
 impl<C: ClientType> ExtClientValidationContext for Ctx<C> {
     fn host_timestamp(&self) -> Result<ibc_core::primitives::Timestamp, ContextError> {
-        Ok(Time::now().into())
+        #[cfg(feature = "std")]
+        let time = self.host_time.unwrap_or_else(Time::now);
+        // without a clock the host must tell the time
+        #[cfg(not(feature = "std"))]
+        let time = self.host_time.ok_or_else(|| ClientError::Other {
+            description: "host time is not set".into(),
+        })?;
+        Ok(time.into())
     }
 
     fn host_height(&self) -> Result<Height, ContextError> {
-        let h = Height::new(0, 1)?;
-        Ok(h)
+        match self.host_height.or_else(|| self.storage.current_height()) {
+            Some(h) => Ok(h),
+            None => height(0, 1),
+        }
     }
 
     fn consensus_state_heights(&self, _client_id: &ClientId) -> Result<Vec<Height>, ContextError> {
-        Ok(self.storage.get_heights())
+        Ok(self.storage.heights())
     }
 
     fn next_consensus_state(
@@ -167,7 +242,9 @@ impl<C: ClientType> ExtClientValidationContext for Ctx<C> {
         _client_id: &ClientId,
         height: &Height,
     ) -> Result<Option<Self::ConsensusStateRef>, ContextError> {
-        Ok(self.storage.get_adjacent_height(height, Direction::Next))
+        Ok(self
+            .storage
+            .adjacent_consensus_state(height, Direction::Next))
     }
 
     fn prev_consensus_state(
@@ -177,7 +254,7 @@ impl<C: ClientType> ExtClientValidationContext for Ctx<C> {
     ) -> Result<Option<Self::ConsensusStateRef>, ContextError> {
         Ok(self
             .storage
-            .get_adjacent_height(height, Direction::Previous))
+            .adjacent_consensus_state(height, Direction::Previous))
     }
 }
 
@@ -187,7 +264,7 @@ mod tests {
 
     use std::{str::FromStr, time::Duration};
 
-    use crate::{api::TendermintClient, utils::base64_to_bytes};
+    use crate::{api::TendermintClient, storage::Storage, utils::base64_to_bytes};
 
     use ibc_client_tendermint::{
         client_state::ClientState,
@@ -199,7 +276,7 @@ mod tests {
 
     use ibc_core::{
         channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
-        client::context::client_state::ClientStateValidation,
+        client::context::client_state::{ClientStateCommon, ClientStateValidation},
         commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
         host::types::{
             identifiers::{ChannelId, PortId, Sequence},
@@ -382,4 +459,50 @@ mod tests {
             .verify_membership(&ibc_prefix, &proof, &root, path, value)
             .expect("pass validate");
     }
+
+    #[test]
+    fn verify_client_message_at_host_time() {
+        let mut client = crate::test_utils::client_state();
+        client.trusting_period = Duration::from_secs(14 * 24 * 60 * 60);
+        client.unbonding_period = Duration::from_secs(21 * 24 * 60 * 60);
+        let client = ClientState::from(client);
+        let client_id = ClientId::new("my_client", 10).unwrap();
+
+        for (host_time, expected) in [
+            (Some("2023-03-10T12:30:00Z"), None),
+            (None, Some("not within trusting period")),
+            (Some("2023-03-10T12:00:00Z"), Some("header from the future")),
+        ] {
+            let mut ctx: Ctx<TendermintClient> =
+                Ctx::default().with_host_time(host_time.map(|t| Time::from_str(t).unwrap()));
+            client
+                .initialise(&mut ctx, &client_id, dummy_consensus_state().into())
+                .unwrap();
+
+            let result = client.verify_client_message(&ctx, &client_id, get_header().into());
+            match expected {
+                None => result.expect("valid at host time"),
+                Some(expected) => {
+                    let err = result.unwrap_err().to_string();
+                    assert!(err.contains(expected), "{host_time:?}: {err}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn host_height_falls_back_to_storage() {
+        let storage = Storage {
+            current_height: Some(Height::new(0, 42).unwrap()),
+            ..Default::default()
+        };
+        let ctx: Ctx<TendermintClient> = Ctx::new(storage);
+        assert_eq!(ctx.host_height().unwrap(), Height::new(0, 42).unwrap());
+
+        let ctx = ctx.with_host_height(Some(Height::new(0, 7).unwrap()));
+        assert_eq!(ctx.host_height().unwrap(), Height::new(0, 7).unwrap());
+
+        let ctx: Ctx<TendermintClient> = Ctx::default();
+        assert_eq!(ctx.host_height().unwrap(), Height::new(0, 1).unwrap());
+    }
 }
@@ -0,0 +1,484 @@
+//! The Tendermint client as an ICS-08 (08-wasm) light client contract. The
+//! 08-wasm module of the host chain calls [`instantiate`], [`sudo`] and
+//! [`query`], and the client keeps its states in the contract store through
+//! [`ContractStore`].
+
+pub mod msg;
+pub mod store;
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+
+use cosmwasm_std::{
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Record, Response,
+    Storage,
+};
+use ibc_client_tendermint::types::{
+    ClientState as ClientStateType, ConsensusState as ConsensusStateType,
+};
+use ibc_core::{
+    client::{
+        context::{
+            client_state::ClientStateCommon,
+            consensus_state::ConsensusState as ConsensusStateTrait, ClientValidationContext,
+            ExtClientValidationContext,
+        },
+        types::{error::ClientError, Height},
+    },
+    commitment_types::{
+        commitment::CommitmentProofBytes,
+        merkle::MerkleProof,
+        proto::{ics23::HostFunctionsManager, v1::MerklePath as RawMerklePath},
+    },
+    host::types::identifiers::{ChainId, ClientId},
+    primitives::proto::Any,
+};
+use prost::Message;
+use serde::Serialize;
+use tendermint::Time;
+
+use crate::{context::Ctx, light_client::LightClient};
+
+use self::{
+    msg::{
+        CheckForMisbehaviourResult, EmptyResult, InstantiateMsg, MerklePath, QueryMsg,
+        StatusResult, SudoMsg, TimestampAtHeightResult, UpdateStateResult,
+    },
+    store::{ContractStore, WasmClient},
+};
+
+fn other(e: impl ToString) -> ClientError {
+    ClientError::Other {
+        description: e.to_string(),
+    }
+}
+
+fn decode_any(bytes: &[u8]) -> Result<Any, ClientError> {
+    Any::decode(bytes).map_err(other)
+}
+
+fn json(result: &impl Serialize) -> Result<Binary, ClientError> {
+    to_json_binary(result).map_err(other)
+}
+
+/// Context evaluating client messages at the time and height of the block.
+fn host_ctx<'a>(store: ContractStore<'a>, env: &Env) -> Result<Ctx<WasmClient<'a>>, ClientError> {
+    let time = env.block.time;
+    let time = Time::from_unix_timestamp(time.seconds() as i64, time.subsec_nanos() as u32)
+        .map_err(other)?;
+    let revision = ChainId::new(&env.block.chain_id)
+        .map_err(other)?
+        .revision_number();
+    Ok(Ctx::new(store)
+        .with_host_time(Some(time))
+        .with_host_height(Some(Height::new(revision, env.block.height)?)))
+}
+
+/// The contract holds a single client, its id only shows in errors.
+fn client_id() -> ClientId {
+    ClientId::new("08-wasm", 0).expect("valid client id")
+}
+
+fn client<'a>(
+    storage: &'a mut dyn Storage,
+    env: &Env,
+) -> Result<LightClient<WasmClient<'a>>, ClientError> {
+    let ctx = host_ctx(ContractStore::new(storage), env)?;
+    Ok(LightClient::new(ctx, client_id()))
+}
+
+/// Store of a query, which must not write.
+struct ReadOnly<'a>(&'a dyn Storage);
+
+impl Storage for ReadOnly<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key)
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        self.0.range(start, end, order)
+    }
+
+    fn set(&mut self, _key: &[u8], _value: &[u8]) {
+        panic!("queries cannot write to the contract store")
+    }
+
+    fn remove(&mut self, _key: &[u8]) {
+        panic!("queries cannot write to the contract store")
+    }
+}
+
+/// Checks that the delay periods have passed since the consensus state at
+/// `height` was stored.
+fn verify_delay_passed(
+    client: &LightClient<WasmClient>,
+    height: Height,
+    delay_time_period: u64,
+    delay_block_period: u64,
+) -> Result<(), ClientError> {
+    let ctx = client.ctx();
+    let (processed_time, processed_height) = ctx
+        .client_update_meta(client.client_id(), &height)
+        .map_err(other)?;
+    let host_time = ctx.host_timestamp().map_err(other)?;
+    let host_height = ctx.host_height().map_err(other)?;
+
+    let valid_time = processed_time
+        .nanoseconds()
+        .saturating_add(delay_time_period);
+    if host_time.nanoseconds() < valid_time {
+        return Err(other(alloc::format!(
+            "delay time period has not passed: host time {} < {valid_time}",
+            host_time.nanoseconds()
+        )));
+    }
+    let valid_height = processed_height.add(delay_block_period);
+    if host_height < valid_height {
+        return Err(other(alloc::format!(
+            "delay block period has not passed: host height {host_height} < {valid_height}"
+        )));
+    }
+    Ok(())
+}
+
+/// Verifies `value` is stored at `merkle_path`, or that nothing is if `value`
+/// is `None`, in the state committed by the consensus state at `height`.
+fn verify_proof(
+    client: &LightClient<WasmClient>,
+    height: Height,
+    proof: Vec<u8>,
+    merkle_path: MerklePath,
+    value: Option<Vec<u8>>,
+) -> Result<(), ClientError> {
+    let client_state = client.client_state()?;
+    if client_state.latest_height() < height {
+        return Err(ClientError::InvalidHeight);
+    }
+    let root = ConsensusStateTrait::root(&client.consensus_state(height)?).clone();
+    let proof =
+        CommitmentProofBytes::try_from(proof).map_err(ClientError::InvalidCommitmentProof)?;
+    let proof = MerkleProof::try_from(&proof).map_err(ClientError::InvalidCommitmentProof)?;
+    let specs = &client_state.inner().proof_specs;
+    let path = RawMerklePath {
+        key_path: merkle_path.key_path,
+    };
+
+    match value {
+        Some(value) => {
+            proof.verify_membership::<HostFunctionsManager>(specs, root.into(), path, value, 0)
+        }
+        None => proof.verify_non_membership::<HostFunctionsManager>(specs, root.into(), path),
+    }
+    .map_err(ClientError::Ics23Verification)
+}
+
+#[entry_point]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ClientError> {
+    let client_state = ClientStateType::try_from(decode_any(&msg.client_state)?)?;
+    let consensus_state = ConsensusStateType::try_from(decode_any(&msg.consensus_state)?)?;
+    let store = ContractStore::with_checksum(deps.storage, msg.checksum.into());
+    LightClient::initialise(
+        host_ctx(store, &env)?,
+        client_id(),
+        client_state,
+        consensus_state,
+    )?;
+    Ok(Response::default())
+}
+
+#[entry_point]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ClientError> {
+    let mut client = client(deps.storage, &env)?;
+    let data = match msg {
+        SudoMsg::UpdateState { client_message } => {
+            let heights = client.update_state(decode_any(&client_message)?)?;
+            json(&UpdateStateResult {
+                heights: heights.into_iter().map(Into::into).collect(),
+            })?
+        }
+        SudoMsg::UpdateStateOnMisbehaviour { client_message } => {
+            client.update_state_on_misbehaviour(decode_any(&client_message)?)?;
+            json(&EmptyResult {})?
+        }
+        SudoMsg::VerifyUpgradeAndUpdateState {
+            upgrade_client_state,
+            upgrade_consensus_state,
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+        } => {
+            client.upgrade(
+                decode_any(&upgrade_client_state)?,
+                decode_any(&upgrade_consensus_state)?,
+                CommitmentProofBytes::try_from(proof_upgrade_client.to_vec())
+                    .map_err(ClientError::InvalidCommitmentProof)?,
+                CommitmentProofBytes::try_from(proof_upgrade_consensus_state.to_vec())
+                    .map_err(ClientError::InvalidCommitmentProof)?,
+            )?;
+            json(&EmptyResult {})?
+        }
+        SudoMsg::VerifyMembership {
+            height,
+            delay_time_period,
+            delay_block_period,
+            proof,
+            merkle_path,
+            value,
+        } => {
+            let height = height.try_into()?;
+            verify_delay_passed(&client, height, delay_time_period, delay_block_period)?;
+            verify_proof(
+                &client,
+                height,
+                proof.into(),
+                merkle_path,
+                Some(value.into()),
+            )?;
+            json(&EmptyResult {})?
+        }
+        SudoMsg::VerifyNonMembership {
+            height,
+            delay_time_period,
+            delay_block_period,
+            proof,
+            merkle_path,
+        } => {
+            let height = height.try_into()?;
+            verify_delay_passed(&client, height, delay_time_period, delay_block_period)?;
+            verify_proof(&client, height, proof.into(), merkle_path, None)?;
+            json(&EmptyResult {})?
+        }
+    };
+    Ok(Response::default().set_data(data))
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ClientError> {
+    let mut storage = ReadOnly(deps.storage);
+    let client = client(&mut storage, &env)?;
+    match msg {
+        QueryMsg::Status {} => json(&StatusResult {
+            status: client.status()?.to_string(),
+        }),
+        QueryMsg::TimestampAtHeight { height } => json(&TimestampAtHeightResult {
+            timestamp: ConsensusStateTrait::timestamp(&client.consensus_state(height.try_into()?)?)
+                .nanoseconds(),
+        }),
+        QueryMsg::VerifyClientMessage { client_message } => {
+            client.verify_client_message(decode_any(&client_message)?)?;
+            json(&EmptyResult {})
+        }
+        QueryMsg::CheckForMisbehaviour { client_message } => json(&CheckForMisbehaviourResult {
+            found_misbehaviour: client.check_for_misbehaviour(decode_any(&client_message)?)?,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use cosmwasm_std::{
+        from_json,
+        testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        OwnedDeps, Timestamp,
+    };
+    use ibc_core::{
+        channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+        primitives::Timestamp as IbcTimestamp,
+    };
+    use ibc_proto::ibc::lightclients::wasm::v1::ClientState as RawWasmClientState;
+
+    use crate::{
+        storage::{ClientStore, Direction},
+        test_utils::{client_state, osmosis_proof},
+        testgen::{ChainSpec, TestChain},
+    };
+
+    use super::msg::JsonHeight;
+
+    type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn env() -> Env {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_nanos(Time::now().unix_timestamp_nanos() as u64);
+        env
+    }
+
+    fn any_bytes(any: impl Into<Any>) -> Binary {
+        any.into().encode_to_vec().into()
+    }
+
+    // A generated chain whose block 6 carries the app hash of the Osmosis proof,
+    // and a contract trusting its height 2.
+    fn instantiated() -> (TestChain, Deps) {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            app_hashes: BTreeMap::from([(6, hex::encode_upper(osmosis_proof().root))]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new("testgen-0").unwrap();
+        client_state.latest_height = chain.ibc_height(2).unwrap();
+
+        let mut deps = mock_dependencies();
+        let info = message_info(&deps.api.addr_make("creator"), &[]);
+        let msg = InstantiateMsg {
+            client_state: any_bytes(client_state),
+            consensus_state: any_bytes(chain.consensus_state(2).unwrap()),
+            checksum: b"checksum".into(),
+        };
+        instantiate(deps.as_mut(), env(), info, msg).unwrap();
+        (chain, deps)
+    }
+
+    fn query_json<T: serde::de::DeserializeOwned>(
+        deps: &Deps,
+        msg: QueryMsg,
+    ) -> Result<T, ClientError> {
+        query(deps.as_ref(), env(), msg).map(|data| from_json(data).unwrap())
+    }
+
+    fn verify_membership(deps: &mut Deps, delay_block_period: u64, value: Vec<u8>) -> bool {
+        let osmosis = osmosis_proof();
+        let msg = SudoMsg::VerifyMembership {
+            height: JsonHeight {
+                revision_number: 0,
+                revision_height: 6,
+            },
+            delay_time_period: 0,
+            delay_block_period,
+            proof: osmosis.proof.clone().into(),
+            merkle_path: MerklePath {
+                key_path: vec!["ibc".to_string(), osmosis.path().to_string()],
+            },
+            value: value.into(),
+        };
+        sudo(deps.as_mut(), env(), msg).is_ok()
+    }
+
+    #[test]
+    fn updates_and_verifies_proofs() {
+        let (chain, mut deps) = instantiated();
+        let status: StatusResult = query_json(&deps, QueryMsg::Status {}).unwrap();
+        assert_eq!(status.status, "Active");
+
+        let header = any_bytes(chain.header(6, 2).unwrap());
+        query_json::<EmptyResult>(
+            &deps,
+            QueryMsg::VerifyClientMessage {
+                client_message: header.clone(),
+            },
+        )
+        .unwrap();
+        let misbehaviour: CheckForMisbehaviourResult = query_json(
+            &deps,
+            QueryMsg::CheckForMisbehaviour {
+                client_message: header.clone(),
+            },
+        )
+        .unwrap();
+        assert!(!misbehaviour.found_misbehaviour);
+
+        let response = sudo(
+            deps.as_mut(),
+            env(),
+            SudoMsg::UpdateState {
+                client_message: header.clone(),
+            },
+        )
+        .unwrap();
+        let result: UpdateStateResult = from_json(response.data.unwrap()).unwrap();
+        let height = chain.ibc_height(6).unwrap();
+        assert_eq!(result.heights, vec![JsonHeight::from(height)]);
+
+        let timestamp: TimestampAtHeightResult = query_json(
+            &deps,
+            QueryMsg::TimestampAtHeight {
+                height: height.into(),
+            },
+        )
+        .unwrap();
+        let expected = chain.consensus_state(6).unwrap().timestamp;
+        assert_eq!(timestamp.timestamp as i128, expected.unix_timestamp_nanos());
+
+        let commitment = compute_packet_commitment(
+            &osmosis_proof().data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &IbcTimestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec();
+        assert!(verify_membership(&mut deps, 0, commitment.clone()));
+        assert!(!verify_membership(&mut deps, 0, b"forged".to_vec()));
+        // the consensus state was stored at the current block
+        assert!(!verify_membership(&mut deps, 10, commitment));
+
+        // the upgrade path of the chain is not in the proof
+        let upgrade = SudoMsg::VerifyUpgradeAndUpdateState {
+            upgrade_client_state: any_bytes(client_state()),
+            upgrade_consensus_state: any_bytes(chain.consensus_state(6).unwrap()),
+            proof_upgrade_client: osmosis_proof().proof.into(),
+            proof_upgrade_consensus_state: osmosis_proof().proof.into(),
+        };
+        assert!(sudo(deps.as_mut(), env(), upgrade).is_err());
+
+        sudo(
+            deps.as_mut(),
+            env(),
+            SudoMsg::UpdateStateOnMisbehaviour {
+                client_message: header,
+            },
+        )
+        .unwrap();
+        let status: StatusResult = query_json(&deps, QueryMsg::Status {}).unwrap();
+        assert_eq!(status.status, "Frozen");
+    }
+
+    #[test]
+    fn stores_states_with_the_ibc_go_layout() {
+        let (chain, mut deps) = instantiated();
+        let any = Any::decode(deps.storage.get(b"clientState").unwrap().as_slice()).unwrap();
+        assert_eq!(any.type_url, "/ibc.lightclients.wasm.v1.ClientState");
+        let wasm = RawWasmClientState::decode(any.value.as_slice()).unwrap();
+        assert_eq!(wasm.checksum, b"checksum");
+        assert_eq!(wasm.latest_height.unwrap().revision_height, 2);
+        assert!(deps.storage.get(b"consensusStates/0-2").is_some());
+        assert_eq!(
+            deps.storage.get(b"consensusStates/0-2/processedHeight"),
+            Some(format!("{}-{}", 14002, env().block.height).into_bytes())
+        );
+
+        // heights are ordered by revision, then height
+        let mut store = ContractStore::new(&mut deps.storage);
+        let cs = chain.consensus_state(2).unwrap();
+        for height in [Height::new(1, 1).unwrap(), Height::new(0, 300).unwrap()] {
+            store.set_consensus_state(height, cs.clone().into());
+        }
+        let heights: Vec<String> = store.heights().iter().map(|h| h.to_string()).collect();
+        assert_eq!(heights, ["0-2", "0-300", "1-1"]);
+
+        let at = |store: &ContractStore, height, direction| {
+            store
+                .adjacent_consensus_state(&Height::new(0, height).unwrap(), direction)
+                .is_some()
+        };
+        assert!(at(&store, 300, Direction::Next));
+        assert!(at(&store, 2, Direction::Next));
+        assert!(!at(&store, 2, Direction::Previous));
+        store.remove_consensus_state(&Height::new(1, 1).unwrap());
+        assert!(!at(&store, 300, Direction::Next));
+        assert!(at(&store, 300, Direction::Previous));
+    }
+}
@@ -0,0 +1,114 @@
+//! JSON messages of the 08-wasm contract API of ibc-go v8. Client and
+//! consensus states, client messages and proofs are protobuf bytes, client
+//! states and consensus states as the `Any` of the Tendermint type.
+
+use alloc::{string::String, vec::Vec};
+
+use cosmwasm_std::Binary;
+use ibc_core::client::types::{error::ClientError, Height};
+use serde::{Deserialize, Serialize};
+
+/// Height as ibc-go encodes it, which omits zero fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonHeight {
+    #[serde(default)]
+    pub revision_number: u64,
+    #[serde(default)]
+    pub revision_height: u64,
+}
+
+impl From<Height> for JsonHeight {
+    fn from(height: Height) -> Self {
+        Self {
+            revision_number: height.revision_number(),
+            revision_height: height.revision_height(),
+        }
+    }
+}
+
+impl TryFrom<JsonHeight> for Height {
+    type Error = ClientError;
+
+    fn try_from(height: JsonHeight) -> Result<Self, ClientError> {
+        Height::new(height.revision_number, height.revision_height)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstantiateMsg {
+    pub client_state: Binary,
+    pub consensus_state: Binary,
+    pub checksum: Binary,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MerklePath {
+    pub key_path: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    UpdateState {
+        client_message: Binary,
+    },
+    UpdateStateOnMisbehaviour {
+        client_message: Binary,
+    },
+    VerifyUpgradeAndUpdateState {
+        upgrade_client_state: Binary,
+        upgrade_consensus_state: Binary,
+        proof_upgrade_client: Binary,
+        proof_upgrade_consensus_state: Binary,
+    },
+    VerifyMembership {
+        height: JsonHeight,
+        delay_time_period: u64,
+        delay_block_period: u64,
+        proof: Binary,
+        merkle_path: MerklePath,
+        value: Binary,
+    },
+    VerifyNonMembership {
+        height: JsonHeight,
+        delay_time_period: u64,
+        delay_block_period: u64,
+        proof: Binary,
+        merkle_path: MerklePath,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Status {},
+    TimestampAtHeight { height: JsonHeight },
+    VerifyClientMessage { client_message: Binary },
+    CheckForMisbehaviour { client_message: Binary },
+}
+
+/// Response data of the sudo messages without a result.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmptyResult {}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStateResult {
+    pub heights: Vec<JsonHeight>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusResult {
+    /// `Active`, `Frozen`, `Expired` or `Unknown`.
+    pub status: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimestampAtHeightResult {
+    /// Unix time in nanoseconds.
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckForMisbehaviourResult {
+    pub found_misbehaviour: bool,
+}
@@ -0,0 +1,212 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{marker::PhantomData, str::FromStr};
+
+use cosmwasm_std::{Order, Record, Storage};
+use ibc_client_tendermint::{
+    client_state::ClientState,
+    consensus_state::ConsensusState,
+    types::{ClientState as ClientStateType, ConsensusState as ConsensusStateType},
+};
+use ibc_core::{
+    client::{context::client_state::ClientStateCommon, types::Height},
+    primitives::{proto::Any, Timestamp},
+};
+use ibc_proto::ibc::lightclients::wasm::v1::{
+    ClientState as RawWasmClientState, ConsensusState as RawWasmConsensusState,
+};
+use prost::Message;
+
+use crate::{
+    context::ClientType,
+    storage::{ClientStore, Direction},
+};
+
+const WASM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ClientState";
+const WASM_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.wasm.v1.ConsensusState";
+
+const CLIENT_STATE_KEY: &[u8] = b"clientState";
+const ITERATE_CONSENSUS_STATES_PREFIX: &[u8] = b"iterateConsensusStates";
+
+fn consensus_state_key(height: &Height) -> Vec<u8> {
+    format!("consensusStates/{height}").into_bytes()
+}
+
+fn processed_time_key(height: &Height) -> Vec<u8> {
+    format!("consensusStates/{height}/processedTime").into_bytes()
+}
+
+fn processed_height_key(height: &Height) -> Vec<u8> {
+    format!("consensusStates/{height}/processedHeight").into_bytes()
+}
+
+/// Key that sorts consensus states by height, revision number first.
+fn iteration_key(height: &Height) -> Vec<u8> {
+    let mut key = ITERATE_CONSENSUS_STATES_PREFIX.to_vec();
+    key.extend(height.revision_number().to_be_bytes());
+    key.extend(height.revision_height().to_be_bytes());
+    key
+}
+
+/// First key after every iteration key.
+fn iteration_end() -> Vec<u8> {
+    let mut end = ITERATE_CONSENSUS_STATES_PREFIX.to_vec();
+    *end.last_mut().expect("non-empty prefix") += 1;
+    end
+}
+
+fn height_from_iteration_key(key: &[u8]) -> Option<Height> {
+    let key = key.strip_prefix(ITERATE_CONSENSUS_STATES_PREFIX)?;
+    let revision_number = u64::from_be_bytes(key.get(..8)?.try_into().ok()?);
+    let revision_height = u64::from_be_bytes(key.get(8..)?.try_into().ok()?);
+    Height::new(revision_number, revision_height).ok()
+}
+
+/// 08-wasm client whose states live in the contract store.
+pub struct WasmClient<'a>(PhantomData<&'a ()>);
+
+impl<'a> ClientType for WasmClient<'a> {
+    type ClientState = ClientState;
+    type ConsensusState = ConsensusState;
+    type Store = ContractStore<'a>;
+}
+
+/// Client store of an 08-wasm contract, with the key layout of ibc-go. States
+/// are stored as the `Any` of their 08-wasm wrapper, whose `data` holds the
+/// `Any` of the Tendermint state.
+pub struct ContractStore<'a> {
+    storage: &'a mut dyn Storage,
+    /// Checksum of the contract code, kept in the stored client state.
+    checksum: Vec<u8>,
+}
+
+impl<'a> ContractStore<'a> {
+    /// Store of a contract whose client state is already stored.
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        let checksum = Self::wasm_client_state(storage)
+            .map(|cs| cs.checksum)
+            .unwrap_or_default();
+        Self { storage, checksum }
+    }
+
+    /// Store of a new contract deployed with code `checksum`.
+    pub fn with_checksum(storage: &'a mut dyn Storage, checksum: Vec<u8>) -> Self {
+        Self { storage, checksum }
+    }
+
+    fn wasm_client_state(storage: &dyn Storage) -> Option<RawWasmClientState> {
+        let any = Any::decode(storage.get(CLIENT_STATE_KEY)?.as_slice()).ok()?;
+        RawWasmClientState::decode(any.value.as_slice()).ok()
+    }
+
+    fn record_height(record: Record) -> Option<Height> {
+        height_from_iteration_key(&record.0)
+    }
+}
+
+impl ClientStore<WasmClient<'_>> for ContractStore<'_> {
+    fn client_state(&self) -> Option<ClientState> {
+        let wasm = Self::wasm_client_state(self.storage)?;
+        let any = Any::decode(wasm.data.as_slice()).ok()?;
+        ClientStateType::try_from(any).ok().map(Into::into)
+    }
+
+    fn set_client_state(&mut self, client_state: ClientState) {
+        let wasm = RawWasmClientState {
+            latest_height: Some(client_state.latest_height().into()),
+            data: Any::from(client_state).encode_to_vec(),
+            checksum: self.checksum.clone(),
+        };
+        let any = Any {
+            type_url: WASM_CLIENT_STATE_TYPE_URL.into(),
+            value: wasm.encode_to_vec(),
+        };
+        self.storage.set(CLIENT_STATE_KEY, &any.encode_to_vec());
+    }
+
+    fn consensus_state(&self, height: &Height) -> Option<ConsensusState> {
+        let bytes = self.storage.get(&consensus_state_key(height))?;
+        let any = Any::decode(bytes.as_slice()).ok()?;
+        let wasm = RawWasmConsensusState::decode(any.value.as_slice()).ok()?;
+        let any = Any::decode(wasm.data.as_slice()).ok()?;
+        ConsensusStateType::try_from(any).ok().map(Into::into)
+    }
+
+    fn set_consensus_state(&mut self, height: Height, consensus_state: ConsensusState) {
+        let wasm = RawWasmConsensusState {
+            data: Any::from(consensus_state).encode_to_vec(),
+        };
+        let any = Any {
+            type_url: WASM_CONSENSUS_STATE_TYPE_URL.into(),
+            value: wasm.encode_to_vec(),
+        };
+        let key = consensus_state_key(&height);
+        self.storage.set(&key, &any.encode_to_vec());
+        self.storage.set(&iteration_key(&height), &key);
+    }
+
+    fn remove_consensus_state(&mut self, height: &Height) {
+        self.storage.remove(&consensus_state_key(height));
+        self.storage.remove(&iteration_key(height));
+    }
+
+    fn heights(&self) -> Vec<Height> {
+        self.storage
+            .range(
+                Some(ITERATE_CONSENSUS_STATES_PREFIX),
+                Some(&iteration_end()),
+                Order::Ascending,
+            )
+            .filter_map(Self::record_height)
+            .collect()
+    }
+
+    fn adjacent_consensus_state(
+        &self,
+        current: &Height,
+        direction: Direction,
+    ) -> Option<ConsensusState> {
+        let current = iteration_key(current);
+        let mut range = match direction {
+            Direction::Next => {
+                // the range start is inclusive, so skip `current` itself
+                let mut start = current;
+                start.push(0);
+                self.storage
+                    .range(Some(&start), Some(&iteration_end()), Order::Ascending)
+            }
+            Direction::Previous => self.storage.range(
+                Some(ITERATE_CONSENSUS_STATES_PREFIX),
+                Some(&current),
+                Order::Descending,
+            ),
+        };
+        range
+            .next()
+            .and_then(Self::record_height)
+            .and_then(|height| self.consensus_state(&height))
+    }
+
+    fn update_meta(&self, height: &Height) -> Option<(Timestamp, Height)> {
+        let time = self.storage.get(&processed_time_key(height))?;
+        let time = u64::from_be_bytes(time.try_into().ok()?);
+        let processed_height = self.storage.get(&processed_height_key(height))?;
+        let processed_height = Height::from_str(&String::from_utf8(processed_height).ok()?).ok()?;
+        Some((Timestamp::from_nanoseconds(time).ok()?, processed_height))
+    }
+
+    fn set_update_meta(&mut self, height: Height, (time, processed_height): (Timestamp, Height)) {
+        self.storage.set(
+            &processed_time_key(&height),
+            &time.nanoseconds().to_be_bytes(),
+        );
+        self.storage.set(
+            &processed_height_key(&height),
+            format!("{processed_height}").as_bytes(),
+        );
+    }
+
+    fn remove_update_meta(&mut self, height: &Height) {
+        self.storage.remove(&processed_time_key(height));
+        self.storage.remove(&processed_height_key(height));
+    }
+}
@@ -0,0 +1,433 @@
+//! C ABI of the light client, declared in `include/tendermint_lightclient.h`.
+//!
+//! Every function returns a [`TMLC_OK`] or error code; the message of the
+//! last error on the calling thread is read with [`tmlc_last_error`]. A
+//! client is created with [`tmlc_client_new`] and owned by the caller until
+//! it is passed to [`tmlc_client_free`]. Input buffers are only read during
+//! the call. A client must not be used by two threads at once.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    str::FromStr,
+};
+
+use ibc_client_tendermint::types::Header;
+use ibc_core::{
+    client::{
+        context::client_state::ClientStateCommon,
+        types::{error::ClientError, Height, Status},
+    },
+    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes},
+    host::types::{
+        identifiers::{ChainId, ClientId},
+        path::Path,
+    },
+    primitives::proto::Protobuf,
+};
+use ibc_proto::ibc::lightclients::tendermint::v1::Header as RawHeader;
+use serde::Deserialize;
+use tendermint::Time;
+
+use crate::{
+    api::TendermintClient,
+    client_options::{parse_duration, parse_trust_threshold, ClientOptions},
+    context::Ctx,
+    light_client::LightClient,
+    utils::ConsensusStateFile,
+};
+
+pub const TMLC_OK: i32 = 0;
+/// A required pointer argument is null.
+pub const TMLC_ERR_NULL_POINTER: i32 = 1;
+/// An argument could not be decoded: the config, a string, a header or a proof.
+pub const TMLC_ERR_INVALID_ARGUMENT: i32 = 2;
+/// The client rejected a header or a proof.
+pub const TMLC_ERR_VERIFICATION: i32 = 3;
+/// The library panicked, the client must not be used anymore.
+pub const TMLC_ERR_PANIC: i32 = 4;
+
+pub const TMLC_STATUS_ACTIVE: i32 = 0;
+pub const TMLC_STATUS_FROZEN: i32 = 1;
+pub const TMLC_STATUS_EXPIRED: i32 = 2;
+pub const TMLC_STATUS_UNAUTHORIZED: i32 = 3;
+
+/// Light client handle, opaque to C.
+pub struct TmlcClient(LightClient);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TmlcHeight {
+    pub revision_number: u64,
+    pub revision_height: u64,
+}
+
+impl From<Height> for TmlcHeight {
+    fn from(height: Height) -> Self {
+        Self {
+            revision_number: height.revision_number(),
+            revision_height: height.revision_height(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TmlcStatus {
+    /// One of the `TMLC_STATUS_*` values.
+    pub status: i32,
+    pub latest_height: TmlcHeight,
+}
+
+/// Client configuration, the JSON object passed to [`tmlc_client_new`]. The
+/// client parameters take the values and defaults of the CLI flags.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClientConfig {
+    chain_id: String,
+    /// Height of `consensus_state`, e.g. `1-5200000`.
+    trusted_height: String,
+    consensus_state: ConsensusStateFile,
+    trust_threshold: Option<String>,
+    trusting_period: Option<String>,
+    unbonding_period: Option<String>,
+    max_clock_drift: Option<String>,
+    /// Time headers are evaluated at, now if unset.
+    host_time: Option<Time>,
+}
+
+struct Error {
+    code: i32,
+    message: String,
+}
+
+impl Error {
+    fn invalid(e: impl ToString) -> Self {
+        Self {
+            code: TMLC_ERR_INVALID_ARGUMENT,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<ClientError> for Error {
+    fn from(e: ClientError) -> Self {
+        Self {
+            code: TMLC_ERR_VERIFICATION,
+            message: e.to_string(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, records its error for [`tmlc_last_error`] and returns its code.
+fn call(f: impl FnOnce() -> Result<(), Error>) -> i32 {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Error {
+            code: TMLC_ERR_PANIC,
+            message: format!("panic: {message}"),
+        })
+    });
+    let (code, message) = match result {
+        Ok(()) => (TMLC_OK, None),
+        // messages with a nul byte are truncated to it
+        Err(e) => (
+            e.code,
+            Some(CString::new(e.message).unwrap_or_else(|e| {
+                let nul = e.nul_position();
+                CString::new(&e.into_vec()[..nul]).expect("no nul byte")
+            })),
+        ),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+fn non_null<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Error> {
+    // SAFETY: the caller passes a valid pointer or null
+    unsafe { ptr.as_ref() }.ok_or_else(|| Error {
+        code: TMLC_ERR_NULL_POINTER,
+        message: format!("`{name}` is null"),
+    })
+}
+
+fn non_null_mut<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, Error> {
+    // SAFETY: the caller passes a valid pointer or null
+    unsafe { ptr.as_mut() }.ok_or_else(|| Error {
+        code: TMLC_ERR_NULL_POINTER,
+        message: format!("`{name}` is null"),
+    })
+}
+
+fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    non_null(ptr, name)?;
+    // SAFETY: the caller passes a nul terminated string
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|e| Error::invalid(format!("`{name}` is not UTF-8: {e}")))
+}
+
+fn bytes_arg<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], Error> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    non_null(ptr, name)?;
+    // SAFETY: the caller passes `len` readable bytes
+    Ok(unsafe { slice::from_raw_parts(ptr, len) })
+}
+
+fn height_arg(height: TmlcHeight) -> Result<Height, Error> {
+    Height::new(height.revision_number, height.revision_height).map_err(Error::invalid)
+}
+
+/// `field` of the config parsed with `parse`, or `default` if it is unset.
+fn optional<T>(
+    value: Option<String>,
+    field: &str,
+    parse: fn(&str) -> Result<T, String>,
+    default: T,
+) -> Result<T, Error> {
+    match value {
+        Some(value) => parse(&value).map_err(|e| Error::invalid(format!("invalid {field}: {e}"))),
+        None => Ok(default),
+    }
+}
+
+fn new_client(config: &str) -> Result<LightClient, Error> {
+    let config: ClientConfig = serde_json::from_str(config).map_err(Error::invalid)?;
+
+    let defaults = ClientOptions::default();
+    let options = ClientOptions {
+        chain_id: ChainId::new(&config.chain_id).map_err(Error::invalid)?,
+        trust_threshold: optional(
+            config.trust_threshold,
+            "trust_threshold",
+            parse_trust_threshold,
+            defaults.trust_threshold,
+        )?,
+        trusting_period: optional(
+            config.trusting_period,
+            "trusting_period",
+            parse_duration,
+            defaults.trusting_period,
+        )?,
+        unbonding_period: optional(
+            config.unbonding_period,
+            "unbonding_period",
+            parse_duration,
+            defaults.unbonding_period,
+        )?,
+        max_clock_drift: optional(
+            config.max_clock_drift,
+            "max_clock_drift",
+            parse_duration,
+            defaults.max_clock_drift,
+        )?,
+    };
+    let client_state = options
+        .client_state(Height::from_str(&config.trusted_height).map_err(Error::invalid)?)
+        .map_err(Error::invalid)?;
+
+    let ctx: Ctx<TendermintClient> = Ctx::default().with_host_time(config.host_time);
+    Ok(LightClient::initialise(
+        ctx,
+        ClientId::new("stand-alone", 0).expect("valid client id"),
+        client_state,
+        config.consensus_state.into(),
+    )?)
+}
+
+/// Creates a client from a JSON config and stores it in `*out`:
+///
+/// ```json
+/// {
+///   "chain_id": "osmosis-1",
+///   "trusted_height": "1-5200000",
+///   "consensus_state": { "root": "...", "timestamp": "...", "next_validators_hash": "..." },
+///   "trusting_period": "14d"
+/// }
+/// ```
+///
+/// `trust_threshold`, `trusting_period`, `unbonding_period`, `max_clock_drift`
+/// and `host_time` are optional.
+///
+/// # Safety
+///
+/// `config` is a nul terminated string and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tmlc_client_new(config: *const c_char, out: *mut *mut TmlcClient) -> i32 {
+    call(|| {
+        let out = non_null_mut(out, "out")?;
+        *out = ptr::null_mut();
+        let client = new_client(str_arg(config, "config")?)?;
+        *out = Box::into_raw(Box::new(TmlcClient(client)));
+        Ok(())
+    })
+}
+
+/// Frees a client created by [`tmlc_client_new`]. Null is ignored.
+///
+/// # Safety
+///
+/// `client` was returned by [`tmlc_client_new`] and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn tmlc_client_free(client: *mut TmlcClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Verifies a protobuf encoded `ibc.lightclients.tendermint.v1.Header` and
+/// installs its consensus state. Its height is stored in `*out_height` unless
+/// it is null.
+///
+/// # Safety
+///
+/// `client` is a live client, `header` points to `header_len` bytes and
+/// `out_height` is null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tmlc_client_update(
+    client: *mut TmlcClient,
+    header: *const u8,
+    header_len: usize,
+    out_height: *mut TmlcHeight,
+) -> i32 {
+    call(|| {
+        let client = non_null_mut(client, "client")?;
+        let header = bytes_arg(header, header_len, "header")?;
+        let header = <Header as Protobuf<RawHeader>>::decode_vec(header).map_err(Error::invalid)?;
+        let height = client.0.update(header)?;
+        if let Some(out) = out_height.as_mut() {
+            *out = height.into();
+        }
+        Ok(())
+    })
+}
+
+/// Proof arguments shared by the membership checks.
+struct ProofArgs {
+    height: Height,
+    prefix: CommitmentPrefix,
+    path: Path,
+    proof: CommitmentProofBytes,
+}
+
+unsafe fn proof_args(
+    height: TmlcHeight,
+    prefix: *const c_char,
+    path: *const c_char,
+    proof: *const u8,
+    proof_len: usize,
+) -> Result<ProofArgs, Error> {
+    Ok(ProofArgs {
+        height: height_arg(height)?,
+        prefix: CommitmentPrefix::try_from(str_arg(prefix, "prefix")?.as_bytes().to_vec())
+            .map_err(Error::invalid)?,
+        path: str_arg(path, "path")?.parse().map_err(Error::invalid)?,
+        proof: CommitmentProofBytes::try_from(bytes_arg(proof, proof_len, "proof")?.to_vec())
+            .map_err(Error::invalid)?,
+    })
+}
+
+/// Verifies that `value` is stored at `path` under `prefix`, e.g. `ibc`, in
+/// the state committed by the consensus state at `height`. `proof` is a
+/// protobuf encoded `ibc.core.commitment.v1.MerkleProof`.
+///
+/// # Safety
+///
+/// `client` is a live client, `prefix` and `path` are nul terminated strings,
+/// `proof` and `value` point to `proof_len` and `value_len` bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn tmlc_client_verify_membership(
+    client: *const TmlcClient,
+    height: TmlcHeight,
+    prefix: *const c_char,
+    path: *const c_char,
+    proof: *const u8,
+    proof_len: usize,
+    value: *const u8,
+    value_len: usize,
+) -> i32 {
+    call(|| {
+        let client = non_null(client, "client")?;
+        let args = proof_args(height, prefix, path, proof, proof_len)?;
+        let value = bytes_arg(value, value_len, "value")?.to_vec();
+        Ok(client
+            .0
+            .verify_membership(args.height, &args.prefix, &args.proof, args.path, value)?)
+    })
+}
+
+/// Verifies that nothing is stored at `path` under `prefix` in the state
+/// committed by the consensus state at `height`.
+///
+/// # Safety
+///
+/// As [`tmlc_client_verify_membership`].
+#[no_mangle]
+pub unsafe extern "C" fn tmlc_client_verify_non_membership(
+    client: *const TmlcClient,
+    height: TmlcHeight,
+    prefix: *const c_char,
+    path: *const c_char,
+    proof: *const u8,
+    proof_len: usize,
+) -> i32 {
+    call(|| {
+        let client = non_null(client, "client")?;
+        let args = proof_args(height, prefix, path, proof, proof_len)?;
+        Ok(client
+            .0
+            .verify_non_membership(args.height, &args.prefix, &args.proof, args.path)?)
+    })
+}
+
+/// Stores the status and latest height of the client in `*out`.
+///
+/// # Safety
+///
+/// `client` is a live client and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tmlc_client_status(
+    client: *const TmlcClient,
+    out: *mut TmlcStatus,
+) -> i32 {
+    call(|| {
+        let client = non_null(client, "client")?;
+        let out = non_null_mut(out, "out")?;
+        let status = match client.0.status()? {
+            Status::Active => TMLC_STATUS_ACTIVE,
+            Status::Frozen => TMLC_STATUS_FROZEN,
+            Status::Expired => TMLC_STATUS_EXPIRED,
+            Status::Unauthorized => TMLC_STATUS_UNAUTHORIZED,
+        };
+        *out = TmlcStatus {
+            status,
+            latest_height: client.0.client_state()?.latest_height().into(),
+        };
+        Ok(())
+    })
+}
+
+/// Message of the last error on this thread, null if the last call
+/// succeeded. The string is owned by the library and valid until another
+/// function of the library is called on this thread.
+#[no_mangle]
+pub extern "C" fn tmlc_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
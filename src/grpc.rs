@@ -0,0 +1,396 @@
+// `tonic::Status` is the error every gRPC method returns.
+#![allow(clippy::result_large_err)]
+
+use std::sync::Arc;
+
+use ibc_client_tendermint::client_state::ClientState;
+use ibc_core::{
+    client::{
+        context::{
+            client_state::{ClientStateCommon, ClientStateValidation},
+            ClientValidationContext, ExtClientValidationContext,
+        },
+        types::Height,
+    },
+    host::types::path::ClientConsensusStatePath,
+    primitives::proto::Any,
+};
+use ibc_proto::{
+    cosmos::base::query::v1beta1::{PageRequest, PageResponse},
+    ibc::core::client::v1::{
+        query_server::{Query, QueryServer},
+        ConsensusStateWithHeight, Height as RawHeight, IdentifiedClientState, Params,
+        QueryClientParamsRequest, QueryClientParamsResponse, QueryClientStateRequest,
+        QueryClientStateResponse, QueryClientStatesRequest, QueryClientStatesResponse,
+        QueryClientStatusRequest, QueryClientStatusResponse, QueryConsensusStateHeightsRequest,
+        QueryConsensusStateHeightsResponse, QueryConsensusStateRequest,
+        QueryConsensusStateResponse, QueryConsensusStatesRequest, QueryConsensusStatesResponse,
+        QueryUpgradedClientStateRequest, QueryUpgradedClientStateResponse,
+        QueryUpgradedConsensusStateRequest, QueryUpgradedConsensusStateResponse,
+    },
+};
+use tokio::{net::TcpListener, sync::RwLockReadGuard};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{batch::BatchVerifier, service::LightClientService};
+
+/// ibc-go `ibc.core.client.v1.Query` service answered from the storage of a
+/// [`LightClientService`]. It holds a single client, queries for another
+/// client id are not found. Proofs are left empty, the proof height is the
+/// latest height of the client.
+pub struct ClientQueryService {
+    service: Arc<LightClientService>,
+}
+
+impl ClientQueryService {
+    pub fn new(service: Arc<LightClientService>) -> Self {
+        Self { service }
+    }
+
+    /// Read access to the client, once `client_id` is checked to be its id.
+    async fn client(&self, client_id: &str) -> Result<RwLockReadGuard<'_, BatchVerifier>, Status> {
+        let verifier = self.service.client.read().await;
+        if verifier.client_id().as_str() != client_id {
+            return Err(Status::not_found(format!("client {client_id} not found")));
+        }
+        Ok(verifier)
+    }
+}
+
+fn internal(e: impl ToString) -> Status {
+    Status::internal(e.to_string())
+}
+
+fn client_state(verifier: &BatchVerifier) -> Result<ClientState, Status> {
+    verifier
+        .ctx()
+        .client_state(verifier.client_id())
+        .map_err(internal)
+}
+
+fn consensus_state(verifier: &BatchVerifier, height: Height) -> Result<Any, Status> {
+    let cs = verifier
+        .ctx()
+        .consensus_state(&ClientConsensusStatePath::new(
+            verifier.client_id().clone(),
+            height.revision_number(),
+            height.revision_height(),
+        ))
+        .map_err(|e| Status::not_found(e.to_string()))?;
+    Ok(cs.into())
+}
+
+/// Sorted heights of every stored consensus state.
+fn heights(verifier: &BatchVerifier) -> Result<Vec<Height>, Status> {
+    let mut heights = verifier
+        .ctx()
+        .consensus_state_heights(verifier.client_id())
+        .map_err(internal)?;
+    heights.sort();
+    Ok(heights)
+}
+
+/// Applies the offset and limit of `page` to `items`. Keys are not supported.
+fn paginate<T>(items: Vec<T>, page: Option<PageRequest>) -> (Vec<T>, Option<PageResponse>) {
+    let total = items.len() as u64;
+    let Some(page) = page else {
+        return (items, None);
+    };
+    let limit = match page.limit {
+        0 => usize::MAX,
+        limit => limit as usize,
+    };
+    let items = items
+        .into_iter()
+        .skip(page.offset as usize)
+        .take(limit)
+        .collect();
+    let response = PageResponse {
+        next_key: vec![],
+        total: if page.count_total { total } else { 0 },
+    };
+    (items, Some(response))
+}
+
+#[tonic::async_trait]
+impl Query for ClientQueryService {
+    async fn client_state(
+        &self,
+        request: Request<QueryClientStateRequest>,
+    ) -> Result<Response<QueryClientStateResponse>, Status> {
+        let verifier = self.client(&request.get_ref().client_id).await?;
+        let client = client_state(&verifier)?;
+        Ok(Response::new(QueryClientStateResponse {
+            proof_height: Some(client.latest_height().into()),
+            client_state: Some(client.into()),
+            proof: vec![],
+        }))
+    }
+
+    async fn client_states(
+        &self,
+        request: Request<QueryClientStatesRequest>,
+    ) -> Result<Response<QueryClientStatesResponse>, Status> {
+        let verifier = self.service.client.read().await;
+        let client = IdentifiedClientState {
+            client_id: verifier.client_id().to_string(),
+            client_state: Some(client_state(&verifier)?.into()),
+        };
+        let (client_states, pagination) = paginate(vec![client], request.into_inner().pagination);
+        Ok(Response::new(QueryClientStatesResponse {
+            client_states,
+            pagination,
+        }))
+    }
+
+    async fn consensus_state(
+        &self,
+        request: Request<QueryConsensusStateRequest>,
+    ) -> Result<Response<QueryConsensusStateResponse>, Status> {
+        let request = request.into_inner();
+        let verifier = self.client(&request.client_id).await?;
+        let latest_height = client_state(&verifier)?.latest_height();
+        let height = if request.latest_height {
+            latest_height
+        } else {
+            Height::new(request.revision_number, request.revision_height)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?
+        };
+        Ok(Response::new(QueryConsensusStateResponse {
+            consensus_state: Some(consensus_state(&verifier, height)?),
+            proof: vec![],
+            proof_height: Some(latest_height.into()),
+        }))
+    }
+
+    async fn consensus_states(
+        &self,
+        request: Request<QueryConsensusStatesRequest>,
+    ) -> Result<Response<QueryConsensusStatesResponse>, Status> {
+        let request = request.into_inner();
+        let verifier = self.client(&request.client_id).await?;
+        let consensus_states = heights(&verifier)?
+            .into_iter()
+            .map(|height| {
+                Ok(ConsensusStateWithHeight {
+                    height: Some(height.into()),
+                    consensus_state: Some(consensus_state(&verifier, height)?),
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+        let (consensus_states, pagination) = paginate(consensus_states, request.pagination);
+        Ok(Response::new(QueryConsensusStatesResponse {
+            consensus_states,
+            pagination,
+        }))
+    }
+
+    async fn consensus_state_heights(
+        &self,
+        request: Request<QueryConsensusStateHeightsRequest>,
+    ) -> Result<Response<QueryConsensusStateHeightsResponse>, Status> {
+        let request = request.into_inner();
+        let verifier = self.client(&request.client_id).await?;
+        let heights = heights(&verifier)?
+            .into_iter()
+            .map(RawHeight::from)
+            .collect();
+        let (consensus_state_heights, pagination) = paginate(heights, request.pagination);
+        Ok(Response::new(QueryConsensusStateHeightsResponse {
+            consensus_state_heights,
+            pagination,
+        }))
+    }
+
+    async fn client_status(
+        &self,
+        request: Request<QueryClientStatusRequest>,
+    ) -> Result<Response<QueryClientStatusResponse>, Status> {
+        let verifier = self.client(&request.get_ref().client_id).await?;
+        let status = client_state(&verifier)?
+            .status(verifier.ctx(), verifier.client_id())
+            .map_err(internal)?;
+        Ok(Response::new(QueryClientStatusResponse {
+            status: status.to_string(),
+        }))
+    }
+
+    async fn client_params(
+        &self,
+        _request: Request<QueryClientParamsRequest>,
+    ) -> Result<Response<QueryClientParamsResponse>, Status> {
+        Ok(Response::new(QueryClientParamsResponse {
+            params: Some(Params {
+                allowed_clients: vec!["07-tendermint".to_string()],
+            }),
+        }))
+    }
+
+    async fn upgraded_client_state(
+        &self,
+        _request: Request<QueryUpgradedClientStateRequest>,
+    ) -> Result<Response<QueryUpgradedClientStateResponse>, Status> {
+        Err(Status::unimplemented(
+            "the light client has no upgrade plan",
+        ))
+    }
+
+    async fn upgraded_consensus_state(
+        &self,
+        _request: Request<QueryUpgradedConsensusStateRequest>,
+    ) -> Result<Response<QueryUpgradedConsensusStateResponse>, Status> {
+        Err(Status::unimplemented(
+            "the light client has no upgrade plan",
+        ))
+    }
+}
+
+/// Serves the client queries of `service` on `listener` until the task is dropped.
+pub async fn serve(
+    listener: TcpListener,
+    service: Arc<LightClientService>,
+) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(QueryServer::new(ClientQueryService::new(service)))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState};
+    use ibc_core::host::types::identifiers::ChainId;
+    use ibc_proto::ibc::core::client::v1::query_client::QueryClient;
+    use tendermint::Time;
+    use tonic::{transport::Channel, Code};
+
+    use crate::{test_utils::client_state, testgen::ChainSpec};
+
+    // A client trusting height 2 of a generated chain, updated to heights 4 and 6.
+    async fn spawn_query_service() -> QueryClient<Channel> {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new("testgen-0").unwrap();
+        client_state.latest_height = chain.ibc_height(2).unwrap();
+
+        let service =
+            LightClientService::new(client_state, chain.consensus_state(2).unwrap()).unwrap();
+        for height in [4, 6] {
+            let header = chain.header(height, 2).unwrap();
+            service.client.write().await.apply(&header).unwrap();
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, Arc::new(service)));
+        QueryClient::connect(url).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn answers_client_queries() {
+        let mut client = spawn_query_service().await;
+        let client_id = "stand-alone-0".to_string();
+
+        let response = client
+            .client_state(QueryClientStateRequest {
+                client_id: client_id.clone(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let client_state = ClientStateType::try_from(response.client_state.unwrap()).unwrap();
+        assert_eq!(client_state.latest_height, Height::new(0, 6).unwrap());
+
+        let response = client
+            .consensus_state(QueryConsensusStateRequest {
+                client_id: client_id.clone(),
+                latest_height: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(ConsensusState::try_from(response.consensus_state.unwrap()).is_ok());
+
+        let response = client
+            .consensus_state_heights(QueryConsensusStateHeightsRequest {
+                client_id: client_id.clone(),
+                pagination: Some(PageRequest {
+                    offset: 1,
+                    limit: 1,
+                    count_total: true,
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            response.consensus_state_heights,
+            vec![RawHeight {
+                revision_number: 0,
+                revision_height: 4,
+            }]
+        );
+        assert_eq!(response.pagination.unwrap().total, 3);
+
+        let response = client
+            .consensus_states(QueryConsensusStatesRequest {
+                client_id: client_id.clone(),
+                pagination: None,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.consensus_states.len(), 3);
+
+        let response = client
+            .client_status(QueryClientStatusRequest {
+                client_id: client_id.clone(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.status, "Active");
+    }
+
+    #[tokio::test]
+    async fn reports_missing_client_and_height() {
+        let mut client = spawn_query_service().await;
+
+        let status = client
+            .client_status(QueryClientStatusRequest {
+                client_id: "07-tendermint-0".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let status = client
+            .consensus_state(QueryConsensusStateRequest {
+                client_id: "stand-alone-0".to_string(),
+                revision_number: 0,
+                revision_height: 5,
+                latest_height: false,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let status = client
+            .upgraded_client_state(QueryUpgradedClientStateRequest {})
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+}
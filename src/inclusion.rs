@@ -0,0 +1,192 @@
+//! Merkle proofs of transactions against the `data_hash` of a block header,
+//! ported from CometBFT's `types/tx.go` and `crypto/merkle/proof.go`.
+
+use alloc::{format, string::ToString, vec::Vec};
+
+use ibc_core::client::types::error::ClientError;
+use tendermint::{
+    crypto::{default::Sha256, Sha256 as _},
+    merkle::{self, MerkleHash},
+    tx, Hash,
+};
+
+/// Longest proof CometBFT accepts.
+const MAX_AUNTS: usize = 100;
+
+/// Hash of a transaction, the key the `/tx` endpoint looks it up by.
+pub fn tx_hash(tx: &[u8]) -> Hash {
+    Hash::Sha256(Sha256::digest(tx))
+}
+
+/// `data_hash` of a block carrying `txs`: the Merkle root of their hashes.
+pub fn data_hash(txs: &[impl AsRef<[u8]>]) -> Hash {
+    Hash::Sha256(merkle::simple_hash_from_byte_vectors::<Sha256>(&leaves(
+        txs,
+    )))
+}
+
+/// Proof of the transaction at `index` in `txs` against their `data_hash`.
+pub fn tx_proof(txs: &[impl AsRef<[u8]>], index: usize) -> Option<tx::Proof> {
+    let tx = txs.get(index)?.as_ref();
+    let leaves = leaves(txs);
+    let mut hasher = Sha256::default();
+    Some(tx::Proof {
+        root_hash: Hash::Sha256(hasher.hash_byte_vectors(&leaves)),
+        data: tx.to_vec(),
+        proof: merkle::Proof {
+            total: txs.len() as u64,
+            index: index as u64,
+            leaf_hash: Hash::Sha256(hasher.leaf_hash(&leaves[index])),
+            aunts: aunts(&mut hasher, &leaves, index)
+                .into_iter()
+                .map(Hash::Sha256)
+                .collect(),
+        },
+    })
+}
+
+/// Verifies that `proof` proves `tx` in a block whose header has `data_hash`.
+pub fn verify_tx_proof(proof: &tx::Proof, tx: &[u8], data_hash: &Hash) -> Result<(), ClientError> {
+    if proof.data != tx {
+        return Err(other("proof is for another transaction"));
+    }
+    if proof.root_hash != *data_hash {
+        return Err(other(&format!(
+            "proof root {} does not match the data hash {data_hash}",
+            proof.root_hash
+        )));
+    }
+
+    let proof = &proof.proof;
+    if proof.aunts.len() > MAX_AUNTS {
+        return Err(other(&format!(
+            "proof has {} aunts, more than {MAX_AUNTS}",
+            proof.aunts.len()
+        )));
+    }
+    let mut hasher = Sha256::default();
+    let leaf_hash = hasher.leaf_hash(&Sha256::digest(tx));
+    if proof.leaf_hash != Hash::Sha256(leaf_hash) {
+        return Err(other("proof leaf hash does not match the transaction"));
+    }
+    let aunts = proof
+        .aunts
+        .iter()
+        .map(sha256)
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| other("proof has an empty aunt"))?;
+    let root = root_from_aunts(&mut hasher, proof.index, proof.total, leaf_hash, &aunts)
+        .ok_or_else(|| other("proof index, total and aunts are inconsistent"))?;
+    if Hash::Sha256(root) != *data_hash {
+        return Err(other("transaction is not in the block"));
+    }
+    Ok(())
+}
+
+fn leaves(txs: &[impl AsRef<[u8]>]) -> Vec<merkle::Hash> {
+    txs.iter().map(Sha256::digest).collect()
+}
+
+/// Roots of the sibling subtrees of `leaves[index]`, from its sibling up to
+/// a child of the root.
+fn aunts(hasher: &mut Sha256, leaves: &[merkle::Hash], index: usize) -> Vec<merkle::Hash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+    let split = leaves.len().next_power_of_two() / 2;
+    let (mut aunts, sibling) = if index < split {
+        (
+            aunts(hasher, &leaves[..split], index),
+            hasher.hash_byte_vectors(&leaves[split..]),
+        )
+    } else {
+        (
+            aunts(hasher, &leaves[split..], index - split),
+            hasher.hash_byte_vectors(&leaves[..split]),
+        )
+    };
+    aunts.push(sibling);
+    aunts
+}
+
+/// Root of a tree of `total` leaves given the leaf at `index` and its aunts,
+/// `None` if they do not fit together.
+fn root_from_aunts(
+    hasher: &mut Sha256,
+    index: u64,
+    total: u64,
+    leaf_hash: merkle::Hash,
+    aunts: &[merkle::Hash],
+) -> Option<merkle::Hash> {
+    if index >= total {
+        return None;
+    }
+    if total == 1 {
+        return aunts.is_empty().then_some(leaf_hash);
+    }
+    let (last, aunts) = aunts.split_last()?;
+    let split = total.checked_next_power_of_two()? / 2;
+    Some(if index < split {
+        let left = root_from_aunts(hasher, index, split, leaf_hash, aunts)?;
+        hasher.inner_hash(left, *last)
+    } else {
+        let right = root_from_aunts(hasher, index - split, total - split, leaf_hash, aunts)?;
+        hasher.inner_hash(*last, right)
+    })
+}
+
+fn sha256(hash: &Hash) -> Option<merkle::Hash> {
+    match hash {
+        Hash::Sha256(hash) => Some(*hash),
+        Hash::None => None,
+    }
+}
+
+fn other(description: &str) -> ClientError {
+    ClientError::Other {
+        description: description.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txs(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| format!("tx-{i}").into_bytes()).collect()
+    }
+
+    #[test]
+    fn proves_every_transaction() {
+        for n in 1..=9 {
+            let txs = txs(n);
+            let data_hash = data_hash(&txs);
+            for (index, tx) in txs.iter().enumerate() {
+                let proof = tx_proof(&txs, index).unwrap();
+                verify_tx_proof(&proof, tx, &data_hash).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_forged_proofs() {
+        let txs = txs(5);
+        let data_hash = data_hash(&txs);
+        let proof = tx_proof(&txs, 3).unwrap();
+
+        assert!(verify_tx_proof(&proof, b"tx-4", &data_hash).is_err());
+        assert!(verify_tx_proof(&proof, &txs[3], &tx_hash(b"other block")).is_err());
+
+        let mut moved = proof.clone();
+        moved.proof.index = 2;
+        assert!(verify_tx_proof(&moved, &txs[3], &data_hash).is_err());
+
+        let mut truncated = proof.clone();
+        truncated.proof.aunts.pop();
+        assert!(verify_tx_proof(&truncated, &txs[3], &data_hash).is_err());
+
+        let mut out_of_range = proof;
+        out_of_range.proof.index = 5;
+        assert!(verify_tx_proof(&out_of_range, &txs[3], &data_hash).is_err());
+    }
+}
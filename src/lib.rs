@@ -1 +1,51 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod api;
+pub mod context;
+pub mod inclusion;
+pub mod light_client;
+pub mod results;
+pub mod storage;
+
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod client_message;
+#[cfg(feature = "std")]
+pub mod client_options;
+#[cfg(feature = "cosmwasm")]
+pub mod cosmwasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod grpc;
+#[cfg(feature = "std")]
+pub mod logging;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod mock_rpc;
+#[cfg(feature = "std")]
+pub mod packet;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "std")]
 pub mod provider;
+#[cfg(feature = "std")]
+pub mod proxy;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod service;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "std")]
+pub mod testgen;
+#[cfg(feature = "std")]
+pub mod utils;
+#[cfg(feature = "std")]
+pub mod witness;
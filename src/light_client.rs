@@ -0,0 +1,466 @@
+use alloc::{format, string::ToString, vec::Vec};
+
+use ibc_client_tendermint::{
+    client_state::{verify_client_message, ClientState},
+    consensus_state::ConsensusState,
+    types::{ClientState as ClientStateType, ConsensusState as ConsensusStateType, Header},
+};
+use ibc_core::{
+    client::{
+        context::{
+            client_state::{ClientStateCommon, ClientStateExecution, ClientStateValidation},
+            ClientValidationContext,
+        },
+        types::{error::ClientError, Height, Status},
+    },
+    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes},
+    handler::types::error::ContextError,
+    host::types::{
+        identifiers::ClientId,
+        path::{ClientConsensusStatePath, Path},
+    },
+    primitives::proto::Any,
+};
+use tendermint::{abci::types::ExecTxResult, crypto::default::Sha256, tx};
+use tendermint_light_client_verifier::{ProdVerifier, Verifier};
+
+use crate::{
+    api::TendermintClient,
+    context::{ClientType, Ctx},
+    inclusion::verify_tx_proof,
+    results::verify_results,
+};
+
+/// Tendermint client stored in a [`Ctx`]. It only verifies and stores what it
+/// is given, without I/O, so it also runs in `no_std` hosts such as a wasm
+/// contract or an enclave.
+pub struct LightClient<C = TendermintClient>
+where
+    C: ClientType<ClientState = ClientState, ConsensusState = ConsensusState>,
+{
+    client_id: ClientId,
+    ctx: Ctx<C>,
+}
+
+fn client_error(e: ContextError) -> ClientError {
+    match e {
+        ContextError::ClientError(e) => e,
+        e => ClientError::Other {
+            description: e.to_string(),
+        },
+    }
+}
+
+impl<C> LightClient<C>
+where
+    C: ClientType<ClientState = ClientState, ConsensusState = ConsensusState>,
+{
+    /// Client stored as `client_id` in `ctx`, trusting `consensus_state` at
+    /// `client_state.latest_height`.
+    pub fn initialise(
+        mut ctx: Ctx<C>,
+        client_id: ClientId,
+        client_state: ClientStateType,
+        consensus_state: ConsensusStateType,
+    ) -> Result<Self, ClientError> {
+        ClientState::from(client_state).initialise(&mut ctx, &client_id, consensus_state.into())?;
+        Ok(Self { client_id, ctx })
+    }
+
+    /// Client already stored as `client_id` in `ctx`.
+    pub fn new(ctx: Ctx<C>, client_id: ClientId) -> Self {
+        Self { client_id, ctx }
+    }
+
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    pub fn ctx(&self) -> &Ctx<C> {
+        &self.ctx
+    }
+
+    pub fn into_ctx(self) -> Ctx<C> {
+        self.ctx
+    }
+
+    pub fn client_state(&self) -> Result<ClientState, ClientError> {
+        self.ctx.client_state(&self.client_id).map_err(client_error)
+    }
+
+    pub fn consensus_state(&self, height: Height) -> Result<ConsensusState, ClientError> {
+        self.ctx
+            .consensus_state(&ClientConsensusStatePath::new(
+                self.client_id.clone(),
+                height.revision_number(),
+                height.revision_height(),
+            ))
+            .map_err(client_error)
+    }
+
+    pub fn status(&self) -> Result<Status, ClientError> {
+        self.client_state()?.status(&self.ctx, &self.client_id)
+    }
+
+    pub fn verify_client_message(&self, message: Any) -> Result<(), ClientError> {
+        self.client_state()?
+            .verify_client_message(&self.ctx, &self.client_id, message)
+    }
+
+    pub fn check_for_misbehaviour(&self, message: Any) -> Result<bool, ClientError> {
+        self.client_state()?
+            .check_for_misbehaviour(&self.ctx, &self.client_id, message)
+    }
+
+    /// Stores the consensus state of a verified header, returns the updated heights.
+    pub fn update_state(&mut self, header: Any) -> Result<Vec<Height>, ClientError> {
+        self.client_state()?
+            .update_state(&mut self.ctx, &self.client_id, header)
+    }
+
+    /// Freezes the client after verified misbehaviour.
+    pub fn update_state_on_misbehaviour(&mut self, misbehaviour: Any) -> Result<(), ClientError> {
+        self.client_state()?.update_state_on_misbehaviour(
+            &mut self.ctx,
+            &self.client_id,
+            misbehaviour,
+        )
+    }
+
+    /// Verifies the upgraded client and consensus states against the upgrade
+    /// store committed at the latest height, then switches the client to
+    /// them. Returns the new latest height.
+    pub fn upgrade(
+        &mut self,
+        upgraded_client_state: Any,
+        upgraded_consensus_state: Any,
+        proof_upgrade_client: CommitmentProofBytes,
+        proof_upgrade_consensus_state: CommitmentProofBytes,
+    ) -> Result<Height, ClientError> {
+        let client_state = self.client_state()?;
+        let root = self
+            .consensus_state(client_state.latest_height())?
+            .inner()
+            .root
+            .clone();
+        client_state.verify_upgrade_client(
+            upgraded_client_state.clone(),
+            upgraded_consensus_state.clone(),
+            proof_upgrade_client,
+            proof_upgrade_consensus_state,
+            &root,
+        )?;
+        client_state.update_state_on_upgrade(
+            &mut self.ctx,
+            &self.client_id,
+            upgraded_client_state,
+            upgraded_consensus_state,
+        )
+    }
+
+    /// Verifies `header`, then stores its consensus state. A header that
+    /// conflicts with a stored consensus state freezes the client. Only an
+    /// active client accepts headers.
+    pub fn update(&mut self, header: Header) -> Result<Height, ClientError> {
+        self.update_with_verifier(header, &ProdVerifier::default())
+    }
+
+    /// [`Self::update`] checking the header with `verifier` instead of the
+    /// `ProdVerifier`, e.g. one that checks commit signatures in parallel.
+    pub fn update_with_verifier(
+        &mut self,
+        header: Header,
+        verifier: &impl Verifier,
+    ) -> Result<Height, ClientError> {
+        self.status()?.verify_is_active()?;
+        let height = header.height();
+        let message: Any = header.into();
+        verify_client_message::<_, Sha256>(
+            self.client_state()?.inner(),
+            &self.ctx,
+            &self.client_id,
+            message.clone(),
+            verifier,
+        )?;
+        if self.check_for_misbehaviour(message.clone())? {
+            self.update_state_on_misbehaviour(message)?;
+            return Err(ClientError::Other {
+                description:
+                    "header conflicts with an installed consensus state, the client is frozen"
+                        .to_string(),
+            });
+        }
+        self.update_state(message)?;
+        Ok(height)
+    }
+
+    /// Verifies that `value` is stored at `path` in the state committed by the
+    /// consensus state at `height`. Only an active client verifies proofs.
+    pub fn verify_membership(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        path: Path,
+        value: Vec<u8>,
+    ) -> Result<(), ClientError> {
+        self.status()?.verify_is_active()?;
+        let root = self.consensus_state(height)?.inner().root.clone();
+        self.client_state()?
+            .verify_membership(prefix, proof, &root, path, value)
+    }
+
+    /// Verifies that nothing is stored at `path` in the state committed by
+    /// the consensus state at `height`.
+    pub fn verify_non_membership(
+        &self,
+        height: Height,
+        prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        path: Path,
+    ) -> Result<(), ClientError> {
+        self.status()?.verify_is_active()?;
+        let root = self.consensus_state(height)?.inner().root.clone();
+        self.client_state()?
+            .verify_non_membership(prefix, proof, &root, path)
+    }
+
+    /// Verifies that `tx` is in the block of `header`, a header matching the
+    /// consensus state stored at its height.
+    pub fn verify_tx_inclusion(
+        &self,
+        header: Header,
+        tx: &[u8],
+        proof: &tx::Proof,
+    ) -> Result<(), ClientError> {
+        let data_hash =
+            header
+                .signed_header
+                .header
+                .data_hash
+                .ok_or_else(|| ClientError::Other {
+                    description: "header has no data hash".to_string(),
+                })?;
+        self.verify_installed_header(header)?;
+        verify_tx_proof(proof, tx, &data_hash)
+    }
+
+    /// Verifies that the transactions of the block before `header`, a header
+    /// matching the consensus state stored at its height, returned `results`.
+    /// Only their code, data and gas are committed to.
+    pub fn verify_block_results(
+        &self,
+        header: Header,
+        results: &[ExecTxResult],
+    ) -> Result<(), ClientError> {
+        let last_results_hash = header
+            .signed_header
+            .header
+            .last_results_hash
+            .ok_or_else(|| ClientError::Other {
+                description: "header has no last results hash".to_string(),
+            })?;
+        self.verify_installed_header(header)?;
+        verify_results(results, &last_results_hash)
+    }
+
+    /// Checks that the client is active and `header` matches the consensus
+    /// state stored at its height. The consensus state does not keep the other
+    /// hashes of the header, so the header is also verified again against its
+    /// trusted height.
+    fn verify_installed_header(&self, header: Header) -> Result<(), ClientError> {
+        self.status()?.verify_is_active()?;
+        let height = header.height();
+        if *self.consensus_state(height)?.inner() != ConsensusStateType::from(header.clone()) {
+            return Err(ClientError::Other {
+                description: format!("header does not match the consensus state at {height}"),
+            });
+        }
+        self.verify_client_message(header.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use ibc_core::{
+        channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+        host::types::identifiers::ChainId,
+        primitives::Timestamp,
+    };
+    use tendermint::Time;
+
+    use crate::{
+        storage::ClientStore,
+        test_utils::{client_state, osmosis_proof},
+        testgen::{ChainSpec, TestChain},
+    };
+
+    // A generated chain whose block 6 carries the app hash of the Osmosis proof,
+    // and a client trusting its height 2.
+    fn client() -> (TestChain, LightClient) {
+        client_of(ChainSpec {
+            app_hashes: BTreeMap::from([(6, hex::encode_upper(osmosis_proof().root))]),
+            ..ChainSpec::default()
+        })
+    }
+
+    // A client trusting height 2 of the chain of `spec`, started an hour ago.
+    fn client_of(spec: ChainSpec) -> (TestChain, LightClient) {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            ..spec
+        }
+        .generate()
+        .unwrap();
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new("testgen-0").unwrap();
+        client_state.latest_height = chain.ibc_height(2).unwrap();
+
+        let client = LightClient::initialise(
+            Ctx::default(),
+            ClientId::new("stand-alone", 0).unwrap(),
+            client_state,
+            chain.consensus_state(2).unwrap(),
+        )
+        .unwrap();
+        (chain, client)
+    }
+
+    #[test]
+    fn updates_and_verifies_proofs() {
+        let (chain, mut client) = client();
+        assert_eq!(client.status().unwrap(), Status::Active);
+
+        let height = client.update(chain.header(6, 2).unwrap()).unwrap();
+        assert_eq!(height, chain.ibc_height(6).unwrap());
+        assert_eq!(client.client_state().unwrap().latest_height(), height);
+        assert!(client.consensus_state(height).is_ok());
+
+        let osmosis = osmosis_proof();
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let proof = CommitmentProofBytes::try_from(osmosis.proof.clone()).unwrap();
+        let commitment = compute_packet_commitment(
+            &osmosis.data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &Timestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec();
+
+        client
+            .verify_membership(height, &prefix, &proof, osmosis.path(), commitment)
+            .unwrap();
+        assert!(client
+            .verify_membership(height, &prefix, &proof, osmosis.path(), b"forged".to_vec())
+            .is_err());
+        assert!(client
+            .verify_non_membership(height, &prefix, &proof, osmosis.path())
+            .is_err());
+        // no consensus state at the trusted height of the proof
+        assert!(client
+            .verify_membership(
+                chain.ibc_height(5).unwrap(),
+                &prefix,
+                &proof,
+                osmosis.path(),
+                vec![]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let (chain, mut client) = client();
+        // trusts a height the client has no consensus state for
+        assert!(client.update(chain.header(6, 3).unwrap()).is_err());
+        assert_eq!(
+            client.ctx().storage().heights(),
+            vec![chain.ibc_height(2).unwrap()]
+        );
+    }
+
+    #[test]
+    fn frozen_client_rejects_headers_and_proofs() {
+        let (chain, mut client) = client();
+        client.update(chain.header(6, 2).unwrap()).unwrap();
+
+        // the same validators sign another block 6
+        let fork = ChainSpec {
+            app_hashes: BTreeMap::new(),
+            ..chain.spec().clone()
+        }
+        .generate()
+        .unwrap();
+        let err = client.update(fork.header(6, 2).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("frozen"), "{err}");
+        assert_eq!(client.status().unwrap(), Status::Frozen);
+
+        let err = client.update(chain.header(7, 6).unwrap()).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ClientError::ClientNotActive {
+                    status: Status::Frozen
+                }
+            ),
+            "{err}"
+        );
+        let osmosis = osmosis_proof();
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+        let proof = CommitmentProofBytes::try_from(osmosis.proof.clone()).unwrap();
+        let err = client
+            .verify_non_membership(
+                chain.ibc_height(6).unwrap(),
+                &prefix,
+                &proof,
+                osmosis.path(),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ClientError::ClientNotActive {
+                    status: Status::Frozen
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn verifies_tx_inclusion() {
+        let (chain, mut client) = client_of(ChainSpec {
+            txs: BTreeMap::from([(
+                6,
+                vec!["aa".to_string(), "bb".to_string(), "cc".to_string()],
+            )]),
+            ..ChainSpec::default()
+        });
+
+        let header = chain.header(6, 2).unwrap();
+        let proof = chain.tx_proof(6, 1).unwrap();
+        // the client has not verified the header yet
+        assert!(client
+            .verify_tx_inclusion(header.clone(), &[0xbb], &proof)
+            .is_err());
+
+        client.update(header.clone()).unwrap();
+        client
+            .verify_tx_inclusion(header.clone(), &[0xbb], &proof)
+            .unwrap();
+        assert!(client
+            .verify_tx_inclusion(header.clone(), &[0xcc], &proof)
+            .is_err());
+
+        // a data hash the validators did not sign
+        let mut forged = header;
+        forged.signed_header.header.data_hash = Some(crate::inclusion::data_hash(&[[0xdd]]));
+        let forged_proof = crate::inclusion::tx_proof(&[[0xdd]], 0).unwrap();
+        assert!(client
+            .verify_tx_inclusion(forged, &[0xdd], &forged_proof)
+            .is_err());
+    }
+}
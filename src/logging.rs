@@ -0,0 +1,135 @@
+use std::error::Error;
+
+use clap::{Args, ValueEnum};
+use tracing::Subscriber;
+use tracing_subscriber::{
+    fmt::{format::FmtSpan, MakeWriter},
+    util::SubscriberInitExt,
+    EnvFilter,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable, multi-line events.
+    #[default]
+    Pretty,
+    /// One JSON object per event.
+    Json,
+}
+
+/// Logging flags shared by every command. Logs go to stderr so they do not mix
+/// with the command output.
+#[derive(Args, Clone, Debug)]
+pub struct LogOptions {
+    /// Log filter, e.g. `debug` or `tendermint_lightclient::provider=trace`.
+    /// Defaults to `RUST_LOG`, then `warn`.
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+}
+
+impl LogOptions {
+    /// Filter from `--log-level`, else `RUST_LOG`, else `warn`.
+    pub fn filter(&self) -> Result<EnvFilter, Box<dyn Error>> {
+        Ok(match &self.log_level {
+            Some(level) => EnvFilter::try_new(level)?,
+            None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        })
+    }
+
+    /// Subscriber writing events to `writer`, along with the duration of each
+    /// span when it closes.
+    pub fn subscriber<W>(
+        &self,
+        writer: W,
+    ) -> Result<Box<dyn Subscriber + Send + Sync>, Box<dyn Error>>
+    where
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(self.filter()?)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(writer);
+        Ok(match self.log_format {
+            LogFormat::Pretty => Box::new(builder.pretty().finish()),
+            LogFormat::Json => Box::new(builder.json().finish()),
+        })
+    }
+
+    /// Installs the global subscriber, writing to stderr.
+    pub fn init(&self) -> Result<(), Box<dyn Error>> {
+        self.subscriber(std::io::stderr)?.try_init()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use clap::Parser;
+    use tracing::{info, info_span};
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        log: LogOptions,
+    }
+
+    #[test]
+    fn parses_flags() {
+        let cli =
+            Cli::try_parse_from(["cli", "--log-level", "debug", "--log-format", "json"]).unwrap();
+        assert_eq!(cli.log.log_format, LogFormat::Json);
+        assert_eq!(cli.log.filter().unwrap().to_string(), "debug");
+
+        let cli = Cli::try_parse_from(["cli", "--log-level", "=!"]).unwrap();
+        assert!(cli.log.filter().is_err());
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logs_events_and_closed_spans() {
+        let cli =
+            Cli::try_parse_from(["cli", "--log-level", "info", "--log-format", "json"]).unwrap();
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = cli.log.subscriber(move || writer.clone()).unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("fetch", height = 5).entered();
+            info!("fetched");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2, "{output}");
+        assert_eq!(lines[0]["fields"]["message"], "fetched");
+        assert_eq!(lines[0]["span"]["height"], 5);
+        assert_eq!(lines[1]["fields"]["message"], "close");
+        assert_eq!(lines[1]["span"]["name"], "fetch");
+        assert!(lines[1]["fields"]["time.busy"].is_string(), "{output}");
+    }
+}
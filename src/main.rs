@@ -1,48 +1,97 @@
 use std::{
     error::Error,
     fs::{self},
+    process::ExitCode,
+    sync::Arc,
     time::Duration,
 };
 
+use tendermint_lightclient::{
+    api, batch, client_message, client_options, context, grpc, logging, mock_rpc, packet, provider,
+    proxy, query, report, service, testgen, utils, witness,
+};
+
 use api::TendermintClient;
-use clap::Parser;
+use batch::{read_headers, verify_batch, BatchOptions};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use client_message::{decode_tx, update_client_messages, verify_client_message, TmClientMessage};
+use client_options::ClientOptions;
 use context::Ctx;
 use ibc_client_tendermint::{
     client_state::ClientState,
-    types::{AllowUpdate, ClientState as ClientStateType, ConsensusState, Header, TrustThreshold},
+    types::{ConsensusState, Header},
 };
+use logging::LogOptions;
+use mock_rpc::MockChain;
+use tendermint::{Hash, Time};
+use testgen::ChainSpec;
+use tracing::{debug, field, info_span, Instrument};
 
+use ibc_core::client::types::Height;
 use ibc_core::{
     client::context::client_state::{
         ClientStateCommon, ClientStateExecution, ClientStateValidation,
     },
-    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+    commitment_types::commitment::{CommitmentPrefix, CommitmentRoot},
     host::types::{
         identifiers::{ChannelId, ClientId, PortId, Sequence},
         path::{CommitmentPath, Path},
     },
 };
-use ibc_core::{
-    client::types::Height, commitment_types::specs::ProofSpecs, host::types::identifiers::ChainId,
+use packet::{read_packet, verify_ack, verify_recv, verify_timeout};
+use provider::LightClientProvider;
+use proxy::VerifyingProxy;
+use query::{query_block_results_verified, query_tx_verified, query_verified};
+use report::{ReportFormat, VerificationReport};
+use service::LightClientService;
+use utils::{
+    bytes_to_base64, fetch_consensus_state, fetch_header, fetch_proof, parse_base64, parse_tx_hash,
+    read_commitment_proof, read_consensus_state, write_consensus_state,
 };
-use utils::{base64_to_bytes, fetch_consensus_state, fetch_header, CSReadable};
-
-mod api;
-mod context;
-mod provider;
-mod storage;
-mod utils;
+use witness::cross_check;
 
 #[derive(Parser, Debug)]
+struct Cli {
+    #[command(flatten)]
+    client: ClientOptions,
+    #[command(flatten)]
+    log: LogOptions,
+    #[command(subcommand)]
+    command: LightClientCli,
+}
+
+#[derive(Subcommand, Debug)]
 enum LightClientCli {
     Verify {
         cs_path: String,
         header_path: String,
+        #[arg(long, value_enum, default_value_t)]
+        format: ReportFormat,
+        /// Verify as of this time instead of now, e.g. 2023-03-10T12:30:00Z.
+        #[arg(long)]
+        at: Option<Time>,
+    },
+    VerifyBatch {
+        cs_path: String,
+        /// Directory of header JSON files, or a file (`-` for stdin) with one JSON header per line.
+        headers_path: String,
+        /// Verify the remaining headers after an invalid one.
+        #[arg(long)]
+        continue_on_failure: bool,
+        /// Check the commit signatures of each header in parallel.
+        #[arg(long)]
+        parallel: bool,
+        /// Verify as of this time instead of now, e.g. 2023-03-10T12:30:00Z.
+        #[arg(long)]
+        at: Option<Time>,
     },
     Update {
         cs_path: String,
         header_path: String,
         new_cs_path: String,
+        /// Verify as of this time instead of now, e.g. 2023-03-10T12:30:00Z.
+        #[arg(long)]
+        at: Option<Time>,
     },
     StateProof {
         proof_path: String,
@@ -60,66 +109,228 @@ enum LightClientCli {
         height: u32,
         output_path: String,
     },
+    FetchProof {
+        url: String,
+        /// IBC store path, e.g. commitments/ports/transfer/channels/channel-0/sequences/1.
+        ibc_path: Path,
+        height: u64,
+        output_path: String,
+    },
+    QueryVerified {
+        url: String,
+        /// Trusted consensus state the client is updated from.
+        cs_path: String,
+        trusted_height: Height,
+        /// IBC store path, e.g. commitments/ports/transfer/channels/channel-0/sequences/1.
+        ibc_path: Path,
+        height: u64,
+        #[arg(long, default_value = "ibc")]
+        prefix: String,
+    },
+    QueryTxVerified {
+        url: String,
+        /// Trusted consensus state the client is updated from.
+        cs_path: String,
+        trusted_height: Height,
+        /// Hex hash of the transaction.
+        #[arg(value_parser = parse_tx_hash)]
+        tx_hash: Hash,
+    },
+    QueryBlockResultsVerified {
+        url: String,
+        /// Trusted consensus state the client is updated from.
+        cs_path: String,
+        trusted_height: Height,
+        height: u64,
+    },
+    CrossCheck {
+        primary_url: String,
+        /// Trusted consensus state the primary header is verified against.
+        cs_path: String,
+        trusted_height: Height,
+        height: u32,
+        /// Full node to compare the primary header with, can be repeated.
+        #[arg(long = "witness", required = true)]
+        witnesses: Vec<String>,
+        /// File the misbehaviour is written to when a witness has a conflicting header.
+        #[arg(long)]
+        evidence_path: Option<String>,
+    },
+    PacketRecv {
+        cs_path: String,
+        packet_path: String,
+        proof_path: String,
+        #[arg(long, default_value = "ibc")]
+        prefix: String,
+    },
+    PacketAck {
+        cs_path: String,
+        packet_path: String,
+        proof_path: String,
+        acknowledgement: String,
+        #[arg(long, default_value = "ibc")]
+        prefix: String,
+    },
+    PacketTimeout {
+        cs_path: String,
+        packet_path: String,
+        proof_path: String,
+        /// Height the consensus state at CS_PATH was installed at, it is trusted as given.
+        proof_height: Height,
+        /// Next sequence to receive on an ordered channel. Unordered channels prove the receipt is absent.
+        #[arg(long)]
+        next_sequence_recv: Option<u64>,
+        #[arg(long, default_value = "ibc")]
+        prefix: String,
+    },
+    Testgen {
+        output_dir: String,
+        /// JSON chain spec, the default spec generates 10 blocks signed by 4 validators.
+        #[arg(long)]
+        spec_path: Option<String>,
+    },
+    VerifyTx {
+        cs_path: String,
+        tx_path: String,
+        /// Consensus state trusted by the second misbehaviour header, if it differs from the first one.
+        #[arg(long)]
+        cs2_path: Option<String>,
+        /// Verify as of this time instead of now, e.g. 2023-03-10T12:30:00Z.
+        #[arg(long)]
+        at: Option<Time>,
+    },
+    MockRpc {
+        /// Address to listen on, e.g. 127.0.0.1:26657.
+        addr: String,
+        /// JSON chain spec to generate the served chain from.
+        #[arg(long, conflicts_with = "chain_path")]
+        spec_path: Option<String>,
+        /// Recorded chain: JSON with `blocks` and optional `abci_queries`.
+        #[arg(long)]
+        chain_path: Option<String>,
+    },
+    Proxy {
+        /// Full node the requests are forwarded to.
+        url: String,
+        /// Trusted consensus state the responses are verified from.
+        cs_path: String,
+        trusted_height: Height,
+        /// Address to listen on, e.g. 127.0.0.1:8080.
+        addr: String,
+    },
+    Serve {
+        /// Trusted consensus state the client starts from.
+        cs_path: String,
+        trusted_height: Height,
+        /// Address to listen on, e.g. 127.0.0.1:8080.
+        addr: String,
+        /// Also serve the ibc-go client gRPC queries on this address, e.g. 127.0.0.1:9090.
+        #[arg(long)]
+        grpc_addr: Option<String>,
+        /// Full node polled for the chain head, reported as the lag in `/metrics`.
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let cli = LightClientCli::parse();
-    let five_year = 5 * 365 * 24 * 60 * 60;
-
-    // TODO: READ it from json file also
-    let client = ClientStateType::new(
-        ChainId::new("ibc-0").unwrap(),
-        TrustThreshold::ONE_THIRD,
-        Duration::new(five_year, 0),
-        Duration::new(five_year + 1, 0),
-        Duration::new(40, 0),
-        Height::new(0, 6).expect("Never fails"),
-        ProofSpecs::cosmos(),
-        vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
-        AllowUpdate {
-            after_expiry: true,
-            after_misbehaviour: true,
-        },
-    )?;
-
-    let client: ClientState = ClientState::from(client);
-    let mut ctx: Ctx<TendermintClient> = Ctx::default();
+async fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Err(e) = cli.log.init() {
+        eprintln!("Error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    // every event of a command is recorded in its span, and so is the error
+    // the command fails with
+    let span = info_span!(
+        "cli",
+        command = matches.subcommand_name().unwrap_or_default(),
+        error = field::Empty,
+    );
+    match run(cli).instrument(span.clone()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            span.record("error", field::display(&e));
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    debug!(args = ?cli.command, "running");
+    let client_state = cli
+        .client
+        .client_state(Height::new(cli.client.chain_id.revision_number(), 6)?)?;
+
+    let client: ClientState = ClientState::from(client_state.clone());
+    let ctx: Ctx<TendermintClient> = Ctx::default();
     let client_id = ClientId::new("stand-alone", 0)?;
 
-    match cli {
+    match cli.command {
         LightClientCli::Verify {
             cs_path,
             header_path,
+            format,
+            at,
         } => {
             // we cannot init and verify in separate command
             // b/c we need storage the consensus state hand latest trusted height.
             // so I do both here. However when we can separate 2 action if we have data base to store context i.e blockchain.
-            let cs_content = fs::read_to_string(cs_path)?;
-            let cs: ConsensusState = serde_json::from_str(&cs_content)?;
-            client.initialise(&mut ctx, &client_id, cs.into())?;
+            let mut ctx = ctx.with_host_time(at);
+            let cs = read_consensus_state(&cs_path)?;
+            client.initialise(&mut ctx, &client_id, cs.clone().into())?;
             let header_content = fs::read_to_string(header_path)?;
             let lc_header: Header = serde_json::from_str(&header_content)?;
-            client.verify_client_message(&ctx, &client_id, lc_header.clone().into())?;
+            let result = client.verify_client_message(&ctx, &client_id, lc_header.clone().into());
+
+            let now = at.unwrap_or_else(Time::now);
+            let report = VerificationReport::new(&client_state, &cs, &lc_header, now, &result)?;
+            println!("{}", report.render(format)?);
+            result?;
+        }
+        LightClientCli::VerifyBatch {
+            cs_path,
+            headers_path,
+            continue_on_failure,
+            parallel,
+            at,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let headers = read_headers(&headers_path)?;
+            let options = BatchOptions {
+                keep_going: continue_on_failure,
+                parallel,
+                at,
+            };
+            let results = verify_batch(client_state.clone(), cs, headers, options)?;
+
+            for result in &results {
+                println!("{result}");
+            }
+            let failed = results.iter().filter(|r| r.error.is_some()).count();
+            if failed > 0 {
+                return Err(format!("{failed} of {} headers are invalid", results.len()).into());
+            }
         }
         LightClientCli::Update {
             cs_path,
             header_path,
             new_cs_path,
+            at,
         } => {
             // TODO: Remove this duplicate code.
-            let cs_content = fs::read_to_string(cs_path)?;
-            let cs: ConsensusState = serde_json::from_str(&cs_content)?;
+            let mut ctx = ctx.with_host_time(at);
+            let cs = read_consensus_state(&cs_path)?;
             client.initialise(&mut ctx, &client_id, cs.into())?;
             let header_content = fs::read_to_string(header_path)?;
             let lc_header: Header = serde_json::from_str(&header_content)?;
             client.verify_client_message(&ctx, &client_id, lc_header.clone().into())?;
             let new_cs = ConsensusState::from(lc_header);
 
-            fs::write(
-                new_cs_path,
-                serde_json::to_string(&CSReadable::from(new_cs)).unwrap(),
-            )?;
+            write_consensus_state(&new_cs_path, new_cs)?;
         }
 
         LightClientCli::StateProof {
@@ -129,13 +340,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
             value,
             prefix,
         } => {
-            let proof_str: String = fs::read_to_string(proof_path).unwrap();
-            let proof_bytes = base64_to_bytes(&proof_str);
-            let proof = CommitmentProofBytes::try_from(proof_bytes)?;
+            let proof = read_commitment_proof(&proof_path)?;
 
-            let app_hash = CommitmentRoot::from_bytes(&base64_to_bytes(&app_hash));
+            let app_hash = CommitmentRoot::from_bytes(
+                &parse_base64(&app_hash).map_err(|e| format!("invalid app hash: {e}"))?,
+            );
 
-            let value = base64_to_bytes(&value).to_vec();
+            let value = parse_base64(&value).map_err(|e| format!("invalid value: {e}"))?;
 
             let port_id = PortId::new("transfer".to_owned()).unwrap();
             let channel_id = ChannelId::new(0);
@@ -155,6 +366,323 @@ async fn main() -> Result<(), Box<dyn Error>> {
             height,
             output_path,
         } => fetch_header(url, output_path, height).await?,
+        LightClientCli::FetchProof {
+            url,
+            ibc_path,
+            height,
+            output_path,
+        } => {
+            let response = fetch_proof(url, ibc_path.clone(), height, output_path).await?;
+            println!(
+                "{ibc_path} at height {}: {}",
+                response.height,
+                bytes_to_base64(&response.value)
+            );
+        }
+        LightClientCli::QueryVerified {
+            url,
+            cs_path,
+            trusted_height,
+            ibc_path,
+            height,
+            prefix,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let provider = LightClientProvider::new(url.parse()?);
+            let prefix = CommitmentPrefix::try_from(prefix.as_bytes().to_vec())?;
+
+            let verified = query_verified(
+                &provider,
+                client_state.clone(),
+                &cs,
+                trusted_height,
+                &prefix,
+                ibc_path.clone(),
+                height,
+            )
+            .await?;
+            if verified.value.is_empty() {
+                println!(
+                    "{ibc_path} at height {} is absent, proven by header {}",
+                    verified.height, verified.proof_height
+                );
+            } else {
+                println!(
+                    "{ibc_path} at height {}, proven by header {}: {}",
+                    verified.height,
+                    verified.proof_height,
+                    bytes_to_base64(&verified.value)
+                );
+            }
+        }
+        LightClientCli::QueryTxVerified {
+            url,
+            cs_path,
+            trusted_height,
+            tx_hash,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let provider = LightClientProvider::new(url.parse()?);
+
+            let verified = query_tx_verified(
+                &provider,
+                client_state.clone(),
+                &cs,
+                trusted_height,
+                tx_hash,
+            )
+            .await?;
+            println!(
+                "tx {tx_hash} is at index {} of block {}: {}",
+                verified.index,
+                verified.height,
+                bytes_to_base64(&verified.tx)
+            );
+        }
+        LightClientCli::QueryBlockResultsVerified {
+            url,
+            cs_path,
+            trusted_height,
+            height,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let provider = LightClientProvider::new(url.parse()?);
+
+            let verified = query_block_results_verified(
+                &provider,
+                client_state.clone(),
+                &cs,
+                trusted_height,
+                height,
+            )
+            .await?;
+            println!(
+                "results of block {} proven by header {}:",
+                verified.height, verified.proof_height
+            );
+            println!("{}", serde_json::to_string_pretty(&verified.results)?);
+        }
+        LightClientCli::CrossCheck {
+            primary_url,
+            cs_path,
+            trusted_height,
+            height,
+            witnesses,
+            evidence_path,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let witnesses = witnesses
+                .iter()
+                .map(|url| url.parse())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let check = cross_check(
+                primary_url.parse()?,
+                witnesses,
+                client_state.clone(),
+                &client_id,
+                &cs,
+                trusted_height,
+                height,
+            )
+            .await?;
+            println!(
+                "primary header {} at height {height} is valid",
+                check.header.signed_header.header.hash()
+            );
+            for (witness, report) in &check.witnesses {
+                println!("{witness}: {report}");
+            }
+
+            let conflicts: Vec<_> = check.conflicts().collect();
+            if !conflicts.is_empty() {
+                if let Some(evidence_path) = evidence_path {
+                    fs::write(evidence_path, serde_json::to_string_pretty(&conflicts)?)?;
+                }
+                return Err(format!("{} witnesses report a fork", conflicts.len()).into());
+            }
+        }
+        LightClientCli::PacketRecv {
+            cs_path,
+            packet_path,
+            proof_path,
+            prefix,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let packet = read_packet(&packet_path)?;
+            let proof = read_commitment_proof(&proof_path)?;
+            let prefix = CommitmentPrefix::try_from(prefix.as_bytes().to_vec())?;
+
+            verify_recv(&client, &prefix, &proof, &cs, &packet)?;
+            println!("packet {packet} commitment is valid");
+        }
+        LightClientCli::PacketAck {
+            cs_path,
+            packet_path,
+            proof_path,
+            acknowledgement,
+            prefix,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let packet = read_packet(&packet_path)?;
+            let proof = read_commitment_proof(&proof_path)?;
+            let prefix = CommitmentPrefix::try_from(prefix.as_bytes().to_vec())?;
+            let ack = parse_base64(&acknowledgement)
+                .map_err(|e| format!("invalid acknowledgement: {e}"))?;
+
+            verify_ack(&client, &prefix, &proof, &cs, &packet, ack)?;
+            println!("packet {packet} acknowledgement is valid");
+        }
+        LightClientCli::PacketTimeout {
+            cs_path,
+            packet_path,
+            proof_path,
+            proof_height,
+            next_sequence_recv,
+            prefix,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let packet = read_packet(&packet_path)?;
+            let proof = read_commitment_proof(&proof_path)?;
+            let prefix = CommitmentPrefix::try_from(prefix.as_bytes().to_vec())?;
+
+            verify_timeout(
+                &client,
+                &prefix,
+                &proof,
+                &cs,
+                proof_height,
+                &packet,
+                next_sequence_recv.map(Sequence::from),
+            )?;
+            println!("packet {packet} timed out");
+        }
+        LightClientCli::Testgen {
+            output_dir,
+            spec_path,
+        } => {
+            let spec = match spec_path {
+                Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+                None => ChainSpec::default(),
+            };
+            spec.generate()?
+                .write_fixtures(std::path::Path::new(&output_dir))?;
+        }
+        LightClientCli::VerifyTx {
+            cs_path,
+            tx_path,
+            cs2_path,
+            at,
+        } => {
+            let mut trusted = vec![read_consensus_state(&cs_path)?];
+            if let Some(cs2_path) = cs2_path {
+                trusted.push(read_consensus_state(&cs2_path)?);
+            }
+
+            let tx = decode_tx(&fs::read(tx_path)?)?;
+            for msg in update_client_messages(&tx)? {
+                let message = TmClientMessage::try_from(msg.client_message)?;
+                let misbehaviour = verify_client_message(
+                    client_state.clone(),
+                    &msg.client_id,
+                    &message,
+                    &trusted,
+                    at,
+                )?;
+
+                let heights: Vec<String> =
+                    message.heights().iter().map(|h| h.to_string()).collect();
+                println!(
+                    "{}: {} at height {} is valid, misbehaviour: {}",
+                    msg.client_id,
+                    message.kind(),
+                    heights.join(", "),
+                    misbehaviour
+                );
+            }
+        }
+        LightClientCli::MockRpc {
+            addr,
+            spec_path,
+            chain_path,
+        } => {
+            let chain = match (spec_path, chain_path) {
+                (_, Some(path)) => MockChain::load(&path)?,
+                (Some(path), None) => {
+                    let spec: ChainSpec = serde_json::from_str(&fs::read_to_string(path)?)?;
+                    MockChain::from(&spec.generate()?)
+                }
+                (None, None) => MockChain::from(&ChainSpec::default().generate()?),
+            };
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            println!("serving mock rpc on http://{}", listener.local_addr()?);
+            mock_rpc::serve(listener, chain).await?;
+        }
+        LightClientCli::Proxy {
+            url,
+            cs_path,
+            trusted_height,
+            addr,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let provider = LightClientProvider::new(url.parse()?);
+            let proxy = VerifyingProxy::new(provider, client_state.clone(), cs, trusted_height)?;
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            println!("serving verified rpc on http://{}", listener.local_addr()?);
+            proxy::serve(listener, proxy).await?;
+        }
+        LightClientCli::Serve {
+            cs_path,
+            trusted_height,
+            addr,
+            grpc_addr,
+            rpc_url,
+        } => {
+            let cs = read_consensus_state(&cs_path)?;
+            let client_state = cli.client.client_state(trusted_height)?;
+            let service = Arc::new(LightClientService::new(client_state, cs)?);
+            let grpc_listener = match grpc_addr {
+                Some(grpc_addr) => {
+                    let listener = tokio::net::TcpListener::bind(&grpc_addr).await?;
+                    println!(
+                        "serving client queries on grpc://{}",
+                        listener.local_addr()?
+                    );
+                    Some(listener)
+                }
+                None => None,
+            };
+            if let Some(rpc_url) = rpc_url {
+                let provider = LightClientProvider::new(rpc_url.parse()?);
+                let service = service.clone();
+                tokio::spawn(
+                    async move {
+                        service
+                            .watch_chain_head(provider, Duration::from_secs(10))
+                            .await
+                    }
+                    .in_current_span(),
+                );
+            }
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            println!("serving light client on http://{}", listener.local_addr()?);
+
+            // either server failing stops the command
+            let http = async {
+                service::serve(listener, service.clone())
+                    .await
+                    .map_err(|e| format!("http server failed: {e}"))
+            };
+            let grpc = async {
+                match grpc_listener {
+                    Some(listener) => grpc::serve(listener, service.clone())
+                        .await
+                        .map_err(|e| format!("grpc server failed: {e}")),
+                    None => Ok(()),
+                }
+            };
+            tokio::try_join!(http, grpc)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,239 @@
+use std::{error::Error, time::Duration};
+
+use ibc_core::{
+    client::{
+        context::{
+            client_state::ClientStateCommon, ClientValidationContext, ExtClientValidationContext,
+        },
+        types::error::ClientError,
+    },
+    handler::types::error::ContextError,
+    host::types::path::ClientConsensusStatePath,
+};
+use prometheus::{
+    Encoder, Gauge, Histogram, HistogramOpts, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+use crate::batch::BatchVerifier;
+
+/// Prometheus metrics of a light client, served on `/metrics` by the `serve`
+/// and `proxy` commands. Clones share the same metrics.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    trusted_height: IntGauge,
+    chain_head_height: IntGauge,
+    chain_head_lag: IntGauge,
+    trusting_period_remaining: Gauge,
+    verification_seconds: Histogram,
+    verification_failures: IntCounterVec,
+    rpc_requests: IntCounterVec,
+    rpc_errors: IntCounterVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let registry = Registry::new_custom(Some("lightclient".to_string()), None)
+            .expect("the prefix is valid");
+        let trusted_height = IntGauge::new("trusted_height", "Latest height trusted by the client")
+            .expect("the metric is valid");
+        let chain_head_height = IntGauge::new(
+            "chain_head_height",
+            "Latest height reported by the full node",
+        )
+        .expect("the metric is valid");
+        let chain_head_lag = IntGauge::new(
+            "chain_head_lag",
+            "Blocks between the chain head and the trusted height",
+        )
+        .expect("the metric is valid");
+        let trusting_period_remaining = Gauge::new(
+            "trusting_period_remaining_seconds",
+            "Time left before the latest consensus state is out of the trusting period",
+        )
+        .expect("the metric is valid");
+        let verification_seconds = Histogram::with_opts(HistogramOpts::new(
+            "verification_duration_seconds",
+            "Time to verify a header and install its consensus state",
+        ))
+        .expect("the metric is valid");
+        let verification_failures = IntCounterVec::new(
+            Opts::new(
+                "verification_failures_total",
+                "Rejected headers by kind of client error",
+            ),
+            &["kind"],
+        )
+        .expect("the metric is valid");
+        let rpc_requests = IntCounterVec::new(
+            Opts::new("rpc_requests_total", "Requests sent to the full node"),
+            &["method"],
+        )
+        .expect("the metric is valid");
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("rpc_errors_total", "Requests to the full node that failed"),
+            &["method"],
+        )
+        .expect("the metric is valid");
+
+        let metrics = Self {
+            registry,
+            trusted_height,
+            chain_head_height,
+            chain_head_lag,
+            trusting_period_remaining,
+            verification_seconds,
+            verification_failures,
+            rpc_requests,
+            rpc_errors,
+        };
+        metrics.register().expect("every metric has a unique name");
+        metrics
+    }
+}
+
+impl Metrics {
+    fn register(&self) -> prometheus::Result<()> {
+        self.registry
+            .register(Box::new(self.trusted_height.clone()))?;
+        self.registry
+            .register(Box::new(self.chain_head_height.clone()))?;
+        self.registry
+            .register(Box::new(self.chain_head_lag.clone()))?;
+        self.registry
+            .register(Box::new(self.trusting_period_remaining.clone()))?;
+        self.registry
+            .register(Box::new(self.verification_seconds.clone()))?;
+        self.registry
+            .register(Box::new(self.verification_failures.clone()))?;
+        self.registry
+            .register(Box::new(self.rpc_requests.clone()))?;
+        self.registry.register(Box::new(self.rpc_errors.clone()))
+    }
+
+    /// Records a header verification that took `elapsed` and failed with `error`, if any.
+    pub fn observe_verification(&self, elapsed: Duration, error: Option<&(dyn Error + 'static)>) {
+        self.verification_seconds.observe(elapsed.as_secs_f64());
+        if let Some(error) = error {
+            self.verification_failures
+                .with_label_values(&[&error_kind(error)])
+                .inc();
+        }
+    }
+
+    /// Records the trusted height of `verifier` and how long its latest
+    /// consensus state stays within the trusting period.
+    pub fn observe_client(&self, verifier: &BatchVerifier) {
+        let (ctx, client_id) = (verifier.ctx(), verifier.client_id());
+        let Ok(client) = ctx.client_state(client_id) else {
+            return;
+        };
+        let height = client.latest_height();
+        self.trusted_height.set(height.revision_height() as i64);
+        self.update_lag();
+
+        let path = ClientConsensusStatePath::new(
+            client_id.clone(),
+            height.revision_number(),
+            height.revision_height(),
+        );
+        if let (Ok(cs), Ok(now)) = (ctx.consensus_state(&path), ctx.host_timestamp()) {
+            let expiry = cs.inner().timestamp.unix_timestamp_nanos()
+                + client.inner().trusting_period.as_nanos() as i128;
+            let remaining = expiry - now.nanoseconds() as i128;
+            self.trusting_period_remaining
+                .set(remaining as f64 / 1_000_000_000.0);
+        }
+    }
+
+    /// Records the latest height of the chain, the lag is measured from it.
+    pub fn observe_chain_head(&self, height: u64) {
+        self.chain_head_height.set(height as i64);
+        self.update_lag();
+    }
+
+    /// Records a request for `method` sent to the full node.
+    pub fn observe_rpc<T, E>(&self, method: &str, result: &Result<T, E>) {
+        self.rpc_requests.with_label_values(&[method]).inc();
+        if result.is_err() {
+            self.rpc_errors.with_label_values(&[method]).inc();
+        }
+    }
+
+    fn update_lag(&self) {
+        if self.chain_head_height.get() > 0 {
+            self.chain_head_lag
+                .set(self.chain_head_height.get() - self.trusted_height.get());
+        }
+    }
+
+    /// Every metric in the Prometheus text format.
+    pub fn encode(&self) -> String {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(buffer).expect("metrics are valid UTF-8")
+    }
+}
+
+/// Name of the `ClientError` variant behind `error`, `Other` for other errors.
+pub fn error_kind(error: &(dyn Error + 'static)) -> String {
+    let client_error = error.downcast_ref::<ClientError>().or_else(|| {
+        match error.downcast_ref::<ContextError>() {
+            Some(ContextError::ClientError(e)) => Some(e),
+            _ => None,
+        }
+    });
+    match client_error {
+        Some(e) => format!("{e:?}")
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        None => "Other".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_core::{client::types::Height, host::types::identifiers::ClientId};
+
+    #[test]
+    fn error_kind_names_client_errors() {
+        let error: Box<dyn Error> = ClientError::HeaderVerificationFailure {
+            reason: "bad".to_string(),
+        }
+        .into();
+        assert_eq!(error_kind(error.as_ref()), "HeaderVerificationFailure");
+
+        let error: Box<dyn Error> =
+            ContextError::ClientError(ClientError::ConsensusStateNotFound {
+                client_id: ClientId::new("stand-alone", 0).unwrap(),
+                height: Height::new(0, 1).unwrap(),
+            })
+            .into();
+        assert_eq!(error_kind(error.as_ref()), "ConsensusStateNotFound");
+
+        let error: Box<dyn Error> = "header conflicts".into();
+        assert_eq!(error_kind(error.as_ref()), "Other");
+    }
+
+    #[test]
+    fn encodes_text_format() {
+        let metrics = Metrics::default();
+        metrics.observe_chain_head(12);
+        metrics.observe_rpc("block", &Err::<(), _>("timeout"));
+        metrics.observe_verification(Duration::from_millis(3), None);
+
+        let text = metrics.encode();
+        assert!(text.contains("lightclient_chain_head_height 12"), "{text}");
+        assert!(
+            text.contains(r#"lightclient_rpc_errors_total{method="block"} 1"#),
+            "{text}"
+        );
+        assert!(text.contains("lightclient_verification_duration_seconds_count 1"));
+    }
+}
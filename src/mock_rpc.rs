@@ -0,0 +1,488 @@
+use std::{collections::BTreeMap, error::Error, fs, sync::Arc};
+
+use axum::{
+    extract::{Path as UriPath, Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tendermint::{abci::types::ExecTxResult, block::Block, block::Id as BlockId, validator};
+use tendermint_rpc::endpoint::{abci_query::AbciQuery, block, commit, tx, validators};
+use tendermint_testgen::light_block::TmLightBlock;
+use tokio::net::TcpListener;
+
+use crate::{
+    inclusion::{tx_hash, tx_proof},
+    testgen::TestChain,
+};
+
+/// Recorded answer to an `abci_query` for `path` and `data`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbciQueryEntry {
+    pub path: String,
+    #[serde(with = "hex::serde")]
+    pub data: Vec<u8>,
+    pub response: AbciQuery,
+}
+
+/// Chain served by the mock RPC server. It is either generated by
+/// [`crate::testgen`] or recorded from a node and loaded from JSON.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MockChain {
+    /// Consecutive light blocks, sorted by height.
+    pub blocks: Vec<TmLightBlock>,
+    #[serde(default)]
+    pub abci_queries: Vec<AbciQueryEntry>,
+    /// Hex transactions of blocks by height, their `data_hash` must match.
+    #[serde(default)]
+    pub txs: BTreeMap<u64, Vec<String>>,
+    /// Results of the transactions of blocks by height.
+    #[serde(default)]
+    pub tx_results: BTreeMap<u64, Vec<ExecTxResult>>,
+}
+
+impl From<&TestChain> for MockChain {
+    fn from(chain: &TestChain) -> Self {
+        Self {
+            blocks: chain.light_blocks().to_vec(),
+            abci_queries: vec![],
+            txs: chain.spec().txs.clone(),
+            tx_results: chain.spec().tx_results.clone(),
+        }
+    }
+}
+
+impl MockChain {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn light_block(&self, height: Option<u64>) -> Result<&TmLightBlock, String> {
+        let latest = self.blocks.last().ok_or("chain is empty")?;
+        let Some(height) = height else {
+            return Ok(latest);
+        };
+        self.blocks
+            .iter()
+            .find(|b| b.signed_header.header.height.value() == height)
+            .ok_or_else(|| {
+                format!(
+                    "height {height} must be less than or equal to the current blockchain height {}",
+                    latest.signed_header.header.height
+                )
+            })
+    }
+
+    fn txs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, String> {
+        self.txs
+            .get(&height)
+            .map(|txs| txs.iter().map(hex::decode).collect::<Result<_, _>>())
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(|e| e.to_string())
+    }
+
+    fn block(&self, height: Option<u64>) -> Result<Value, String> {
+        let lb = self.light_block(height)?;
+        let txs = self.txs_at(lb.signed_header.header.height.value())?;
+        let header = lb.signed_header.header.clone();
+        let last_commit = header
+            .height
+            .value()
+            .checked_sub(1)
+            .and_then(|h| self.light_block(Some(h)).ok())
+            .map(|prev| prev.signed_header.commit.clone());
+
+        to_value(block::Response {
+            block_id: BlockId {
+                hash: header.hash(),
+                part_set_header: lb.signed_header.commit.block_id.part_set_header,
+            },
+            block: Block::new_unchecked(header, txs, Default::default(), last_commit),
+        })
+    }
+
+    fn commit(&self, height: Option<u64>) -> Result<Value, String> {
+        let lb = self.light_block(height)?;
+        to_value(commit::Response {
+            signed_header: lb.signed_header.clone(),
+            canonical: true,
+        })
+    }
+
+    fn validators(
+        &self,
+        height: Option<u64>,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Value, String> {
+        let lb = self.light_block(height)?;
+        let all = lb.validators.validators();
+        let skip = page
+            .saturating_sub(1)
+            .checked_mul(per_page)
+            .ok_or_else(|| format!("page {page} of {per_page} validators is out of range"))?;
+        let page: Vec<validator::Info> = all.iter().skip(skip).take(per_page).cloned().collect();
+
+        to_value(validators::Response::new(
+            lb.signed_header.header.height,
+            page,
+            all.len() as i32,
+        ))
+    }
+
+    fn status(&self) -> Result<Value, String> {
+        let earliest = &self.blocks.first().ok_or("chain is empty")?.signed_header;
+        let latest_block = self.light_block(None)?;
+        let latest = &latest_block.signed_header;
+
+        Ok(json!({
+            "node_info": {
+                "protocol_version": { "p2p": "8", "block": "11", "app": "0" },
+                "id": "badfadad0befeedc0c0adeadbeefc0ffeefacade",
+                "listen_addr": "tcp://0.0.0.0:26656",
+                "network": latest.header.chain_id,
+                "version": "0.37.0",
+                "channels": "40202122233038606100",
+                "moniker": "mock",
+                "other": { "tx_index": "on", "rpc_address": "tcp://0.0.0.0:26657" },
+            },
+            "sync_info": {
+                "earliest_block_hash": earliest.header.hash(),
+                "earliest_app_hash": hex::encode_upper(earliest.header.app_hash.as_bytes()),
+                "earliest_block_height": earliest.header.height,
+                "earliest_block_time": earliest.header.time,
+                "latest_block_hash": latest.header.hash(),
+                "latest_app_hash": hex::encode_upper(latest.header.app_hash.as_bytes()),
+                "latest_block_height": latest.header.height,
+                "latest_block_time": latest.header.time,
+                "catching_up": false,
+            },
+            "validator_info": latest_block.validators.validators().first(),
+        }))
+    }
+
+    fn block_results(&self, height: Option<u64>) -> Result<Value, String> {
+        let lb = self.light_block(height)?;
+        let height = lb.signed_header.header.height;
+        Ok(json!({
+            "height": height,
+            "txs_results": self.tx_results.get(&height.value()),
+            "begin_block_events": null,
+            "end_block_events": null,
+            "validator_updates": null,
+            "consensus_param_updates": null,
+        }))
+    }
+
+    fn abci_query(&self, path: &str, data: &[u8], height: Option<u64>) -> Result<Value, String> {
+        let height = self.light_block(height)?.signed_header.header.height;
+        let response = self
+            .abci_queries
+            .iter()
+            .find(|q| q.path == path && q.data == data)
+            .map(|q| q.response.clone())
+            .unwrap_or_else(|| AbciQuery {
+                code: 1.into(),
+                log: "not found".to_string(),
+                key: data.to_vec(),
+                height,
+                ..Default::default()
+            });
+        Ok(json!({ "response": response }))
+    }
+
+    fn tx(&self, hash: &[u8]) -> Result<Value, String> {
+        for &height in self.txs.keys() {
+            let txs = self.txs_at(height)?;
+            let Some(index) = txs.iter().position(|tx| tx_hash(tx).as_bytes() == hash) else {
+                continue;
+            };
+            return to_value(tx::Response {
+                hash: tx_hash(&txs[index]),
+                height: height
+                    .try_into()
+                    .map_err(|e: tendermint::Error| e.to_string())?,
+                index: index as u32,
+                tx_result: Default::default(),
+                tx: txs[index].clone(),
+                proof: tx_proof(&txs, index),
+            });
+        }
+        Err(format!("tx ({}) not found", hex::encode_upper(hash)))
+    }
+
+    fn handle(&self, method: &str, params: &Value) -> Result<Value, String> {
+        let height = param_u64(params, "height");
+        match method {
+            "status" => self.status(),
+            "block" => self.block(height),
+            "commit" => self.commit(height),
+            "validators" => self.validators(
+                height,
+                param_u64(params, "page").unwrap_or(1) as usize,
+                param_u64(params, "per_page").unwrap_or(30) as usize,
+            ),
+            "block_results" => self.block_results(height),
+            "abci_query" => {
+                let path = params
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let data = params
+                    .get("data")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let data = hex::decode(data).map_err(|e| e.to_string())?;
+                self.abci_query(path, &data, height.filter(|h| *h != 0))
+            }
+            "tx" => {
+                let hash = params
+                    .get("hash")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let hash = base64::engine::general_purpose::STANDARD
+                    .decode(hash)
+                    .map_err(|e| e.to_string())?;
+                self.tx(&hash)
+            }
+            other => Err(format!("method {other} is not served by the mock")),
+        }
+    }
+}
+
+pub(crate) fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+/// Reads an integer param, sent as a string or a number by JSON-RPC clients.
+pub(crate) fn param_u64(params: &Value, name: &str) -> Option<u64> {
+    match params.get(name)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RpcRequest {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// JSON-RPC response to the request `id`, errors are reported as internal errors.
+pub(crate) fn rpc_response(id: Value, result: Result<Value, String>) -> Json<Value> {
+    Json(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(data) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32603, "message": "Internal error", "data": data },
+        }),
+    })
+}
+
+/// JSON-RPC params of a URI request like `/block?height=5`. URIs quote
+/// strings and write bytes as `0x` hex, JSON-RPC takes `data` as hex and a
+/// tx `hash` as base64.
+fn uri_params(query: BTreeMap<String, String>) -> Result<Value, String> {
+    let mut params = serde_json::Map::new();
+    for (name, value) in query {
+        let value = if let Some(s) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            match name.as_str() {
+                "data" => hex::encode(s),
+                _ => s.to_string(),
+            }
+        } else if let Some(hex_value) = value.strip_prefix("0x") {
+            let bytes = hex::decode(hex_value).map_err(|e| format!("invalid {name}: {e}"))?;
+            match name.as_str() {
+                "hash" => base64::engine::general_purpose::STANDARD.encode(bytes),
+                _ => hex::encode(bytes),
+            }
+        } else {
+            value
+        };
+        params.insert(name, Value::String(value));
+    }
+    Ok(Value::Object(params))
+}
+
+async fn handle(State(chain): State<Arc<MockChain>>, Json(req): Json<RpcRequest>) -> Json<Value> {
+    rpc_response(req.id, chain.handle(&req.method, &req.params))
+}
+
+async fn handle_uri(
+    State(chain): State<Arc<MockChain>>,
+    UriPath(method): UriPath<String>,
+    Query(query): Query<BTreeMap<String, String>>,
+) -> Json<Value> {
+    let result = uri_params(query).and_then(|params| chain.handle(&method, &params));
+    rpc_response(json!(-1), result)
+}
+
+/// Router answering Tendermint RPC requests for `/block`, `/commit`,
+/// `/validators`, `/status`, `/block_results`, `/abci_query` and `/tx` from
+/// `chain`, as JSON-RPC posted to `/` or as URI requests like
+/// `GET /block?height=5`.
+pub fn router(chain: MockChain) -> Router {
+    Router::new()
+        .route("/", post(handle))
+        .route("/:method", get(handle_uri))
+        .with_state(Arc::new(chain))
+}
+
+/// Serves `chain` on `listener` until the task is dropped.
+pub async fn serve(listener: TcpListener, chain: MockChain) -> std::io::Result<()> {
+    axum::serve(listener, router(chain)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_core::host::types::{
+        identifiers::{ChannelId, PortId, Sequence},
+        path::{CommitmentPath, Path},
+    };
+    use tendermint_rpc::{Client, HttpClient};
+
+    use crate::{
+        provider::{LightClientProvider, IBC_QUERY_PATH},
+        test_utils::{osmosis_proof, spawn_mock_rpc},
+        testgen::ChainSpec,
+    };
+
+    #[tokio::test]
+    async fn provider_against_mock() {
+        let chain = ChainSpec::default().generate().unwrap();
+        let url = spawn_mock_rpc(MockChain::from(&chain)).await;
+        let provider = LightClientProvider::new(url.parse().unwrap());
+
+        assert_eq!(provider.latest_height().await.unwrap(), 10);
+
+        let header = provider.light_header(5).await;
+        let expected = chain.header(5, 4).unwrap();
+        assert_eq!(header.signed_header, expected.signed_header);
+        assert_eq!(header.validator_set.hash(), expected.validator_set.hash());
+
+        let cs = provider.consensus_state(5).await;
+        assert_eq!(cs, chain.consensus_state(5).unwrap());
+    }
+
+    #[tokio::test]
+    async fn status_and_paging() {
+        let spec = ChainSpec {
+            validators: (0..45)
+                .map(|i| crate::testgen::ValidatorSpec {
+                    id: format!("v{i}"),
+                    power: 1,
+                    key_type: crate::testgen::KeyType::Ed25519,
+                })
+                .collect(),
+            ..ChainSpec::default()
+        };
+        let chain = spec.generate().unwrap();
+        let url = spawn_mock_rpc(MockChain::from(&chain)).await;
+        let client = HttpClient::new(url.as_str()).unwrap();
+
+        let status = client.status().await.unwrap();
+        assert_eq!(status.sync_info.latest_block_height.value(), 10);
+        assert_eq!(status.node_info.network.as_str(), "testgen-0");
+
+        let validators = client
+            .validators(3u32, tendermint_rpc::Paging::All)
+            .await
+            .unwrap();
+        assert_eq!(validators.validators.len(), 45);
+
+        assert!(client.block(11u32).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn serves_uri_requests() {
+        let chain = ChainSpec {
+            txs: BTreeMap::from([(4, vec!["aa".to_string()])]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let url = spawn_mock_rpc(MockChain::from(&chain)).await;
+        let get = |path: String| {
+            let url = format!("{url}{path}");
+            async move {
+                reqwest::get(url)
+                    .await
+                    .unwrap()
+                    .json::<Value>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let block = get("/block?height=5".to_string()).await;
+        assert_eq!(block["result"]["block"]["header"]["height"], "5");
+        assert_eq!(
+            get("/status".to_string()).await["result"]["node_info"]["network"],
+            "testgen-0"
+        );
+
+        let tx = get(format!("/tx?hash=0x{}", tx_hash(&[0xaa]))).await;
+        assert_eq!(tx["result"]["height"], "4");
+
+        let query = get(format!(
+            "/abci_query?path=%22{IBC_QUERY_PATH}%22&data=%22missing%22&height=5"
+        ))
+        .await;
+        assert_eq!(
+            query["result"]["response"]["key"],
+            base64::engine::general_purpose::STANDARD.encode("missing")
+        );
+
+        let out_of_range = get(format!(
+            "/validators?height=3&page={}&per_page=30",
+            u64::MAX
+        ))
+        .await;
+        assert!(out_of_range["error"]["data"]
+            .as_str()
+            .unwrap()
+            .contains("out of range"));
+    }
+
+    #[tokio::test]
+    async fn query_ibc_proof_from_proof_ops() {
+        let osmosis = osmosis_proof();
+        let path = osmosis.path();
+        let mut chain = MockChain::from(&ChainSpec::default().generate().unwrap());
+        chain.abci_queries.push(AbciQueryEntry {
+            path: IBC_QUERY_PATH.to_string(),
+            data: path.to_string().into_bytes(),
+            response: AbciQuery {
+                value: vec![1],
+                proof: Some(osmosis.proof_ops()),
+                height: 5u32.into(),
+                ..Default::default()
+            },
+        });
+
+        let url = spawn_mock_rpc(chain).await;
+        let provider = LightClientProvider::new(url.parse().unwrap());
+
+        let response = provider.query_ibc_proof(&path, 5).await.unwrap();
+        assert_eq!(response.value, vec![1]);
+        assert_eq!(response.height, 5);
+        assert_eq!(response.proof, osmosis.proof.try_into().unwrap());
+
+        let missing = Path::Commitment(CommitmentPath::new(
+            &PortId::transfer(),
+            &ChannelId::new(0),
+            Sequence::from(1),
+        ));
+        assert!(provider.query_ibc_proof(&missing, 5).await.is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{error::Error, fs};
+
+use ibc_client_tendermint::{client_state::ClientState, types::ConsensusState};
+use ibc_core::{
+    channel::types::{
+        acknowledgement::Acknowledgement,
+        commitment::{compute_ack_commitment, compute_packet_commitment},
+        packet::Packet,
+    },
+    client::{context::client_state::ClientStateCommon, types::Height},
+    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes},
+    host::types::{
+        identifiers::Sequence,
+        path::{AckPath, CommitmentPath, Path, ReceiptPath, SeqRecvPath},
+    },
+    primitives::Timestamp,
+};
+use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
+
+/// Reads a packet from a JSON file using the ibc-go field names, as shown for
+/// `MsgRecvPacket.packet` by explorers:
+///
+/// ```json
+/// {
+///     "sequence": "3514632",
+///     "source_port": "transfer",
+///     "source_channel": "channel-0",
+///     "destination_port": "transfer",
+///     "destination_channel": "channel-141",
+///     "data": "<base64>",
+///     "timeout_height": { "revision_number": "4", "revision_height": "21413739" },
+///     "timeout_timestamp": "0"
+/// }
+/// ```
+pub fn read_packet(path: &str) -> Result<Packet, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let raw: RawPacket = serde_json::from_str(&content)?;
+    Ok(Packet::try_from(raw)?)
+}
+
+/// Verifies on the destination chain that the source chain committed to `packet`.
+/// `cs` is the trusted consensus state of the source chain.
+pub fn verify_recv(
+    client: &ClientState,
+    prefix: &CommitmentPrefix,
+    proof: &CommitmentProofBytes,
+    cs: &ConsensusState,
+    packet: &Packet,
+) -> Result<(), Box<dyn Error>> {
+    let commitment = compute_packet_commitment(
+        &packet.data,
+        &packet.timeout_height_on_b,
+        &packet.timeout_timestamp_on_b,
+    );
+    let path = CommitmentPath::new(&packet.port_id_on_a, &packet.chan_id_on_a, packet.seq_on_a);
+
+    client
+        .verify_membership(
+            prefix,
+            proof,
+            &cs.root,
+            Path::Commitment(path),
+            commitment.into_vec(),
+        )
+        .map_err(|e| format!("packet commitment verification failed: {e}"))?;
+    Ok(())
+}
+
+/// Verifies on the source chain that the destination chain wrote `ack` for `packet`.
+/// `cs` is the trusted consensus state of the destination chain.
+pub fn verify_ack(
+    client: &ClientState,
+    prefix: &CommitmentPrefix,
+    proof: &CommitmentProofBytes,
+    cs: &ConsensusState,
+    packet: &Packet,
+    ack: Vec<u8>,
+) -> Result<(), Box<dyn Error>> {
+    let ack = Acknowledgement::try_from(ack)?;
+    let commitment = compute_ack_commitment(&ack);
+    let path = AckPath::new(&packet.port_id_on_b, &packet.chan_id_on_b, packet.seq_on_a);
+
+    client
+        .verify_membership(
+            prefix,
+            proof,
+            &cs.root,
+            Path::Ack(path),
+            commitment.into_vec(),
+        )
+        .map_err(|e| format!("acknowledgement verification failed: {e}"))?;
+    Ok(())
+}
+
+/// Verifies on the source chain that `packet` timed out without being received
+/// on the destination chain. `cs` is the trusted consensus state of the
+/// destination chain at `proof_height`.
+///
+/// A consensus state does not record its own height, so `proof_height` is not
+/// checked against `cs`: the caller must pass the height `cs` was installed at
+/// in the client. Any other height lets a packet time out early or late.
+///
+/// For unordered channels the proof shows the packet receipt is absent. For
+/// ordered channels, pass `next_sequence_recv`, the proof then shows the
+/// destination chain still expects a sequence not after the packet's.
+pub fn verify_timeout(
+    client: &ClientState,
+    prefix: &CommitmentPrefix,
+    proof: &CommitmentProofBytes,
+    cs: &ConsensusState,
+    proof_height: Height,
+    packet: &Packet,
+    next_sequence_recv: Option<Sequence>,
+) -> Result<(), Box<dyn Error>> {
+    let timestamp: Timestamp = cs.timestamp().into();
+    if !packet.timed_out(&timestamp, proof_height) {
+        return Err(format!(
+            "packet has not timed out at height {proof_height} (timestamp {timestamp})"
+        )
+        .into());
+    }
+
+    match next_sequence_recv {
+        None => {
+            let path =
+                ReceiptPath::new(&packet.port_id_on_b, &packet.chan_id_on_b, packet.seq_on_a);
+            client
+                .verify_non_membership(prefix, proof, &cs.root, Path::Receipt(path))
+                .map_err(|e| format!("packet receipt absence verification failed: {e}"))?;
+        }
+        Some(next_sequence_recv) => {
+            if packet.seq_on_a < next_sequence_recv {
+                return Err(format!(
+                    "packet sequence {} was already received, next sequence is {next_sequence_recv}",
+                    packet.seq_on_a
+                )
+                .into());
+            }
+            let path = SeqRecvPath::new(&packet.port_id_on_b, &packet.chan_id_on_b);
+            // ibc-go stores sequences as big endian u64.
+            let value = next_sequence_recv.value().to_be_bytes().to_vec();
+            client
+                .verify_membership(prefix, proof, &cs.root, Path::SeqRecv(path), value)
+                .map_err(|e| format!("next sequence recv verification failed: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use ibc_core::commitment_types::commitment::CommitmentRoot;
+    use serde::Deserialize;
+    use tendermint::Time;
+
+    use crate::{
+        test_utils::{client_state, dummy_consensus_state},
+        utils::base64_to_bytes,
+    };
+
+    #[derive(Deserialize)]
+    struct ProofData {
+        proof_commitment: String,
+        root: String,
+    }
+
+    fn packet() -> Packet {
+        read_packet(concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/packet.json")).unwrap()
+    }
+
+    // Consensus state of Osmosis whose root is the app hash the proof fixture was made against.
+    fn proof_and_consensus_state() -> (CommitmentProofBytes, ConsensusState) {
+        let proof_data = serde_json::from_str::<ProofData>(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/data/proof.json"
+        )))
+        .unwrap();
+
+        let proof =
+            CommitmentProofBytes::try_from(base64_to_bytes(&proof_data.proof_commitment)).unwrap();
+        let mut cs = dummy_consensus_state();
+        cs.root = CommitmentRoot::from_bytes(&base64_to_bytes(&proof_data.root));
+        (proof, cs)
+    }
+
+    fn prefix() -> CommitmentPrefix {
+        CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap()
+    }
+
+    #[test]
+    fn verify_recv_packet() {
+        let client = ClientState::from(client_state());
+        let (proof, cs) = proof_and_consensus_state();
+
+        verify_recv(&client, &prefix(), &proof, &cs, &packet()).expect("valid commitment");
+
+        let mut tampered = packet();
+        tampered.data = b"{}".to_vec();
+        assert!(verify_recv(&client, &prefix(), &proof, &cs, &tampered).is_err());
+    }
+
+    #[test]
+    fn ack_proof_is_bound_to_ack_path() {
+        let client = ClientState::from(client_state());
+        let (proof, cs) = proof_and_consensus_state();
+
+        let err = verify_ack(
+            &client,
+            &prefix(),
+            &proof,
+            &cs,
+            &packet(),
+            b"{\"result\":\"AQ==\"}".to_vec(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("acknowledgement verification failed"));
+    }
+
+    #[test]
+    fn timeout_requires_expired_packet() {
+        let client = ClientState::from(client_state());
+        let (proof, mut cs) = proof_and_consensus_state();
+        cs.timestamp = Time::from_str("2024-01-01T00:00:00Z").unwrap();
+
+        let err = verify_timeout(
+            &client,
+            &prefix(),
+            &proof,
+            &cs,
+            Height::new(4, 21413739).unwrap(),
+            &packet(),
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("packet has not timed out"));
+
+        // Past the timeout height, but the proof is a membership proof of the commitment.
+        let err = verify_timeout(
+            &client,
+            &prefix(),
+            &proof,
+            &cs,
+            Height::new(4, 21413740).unwrap(),
+            &packet(),
+            None,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("packet receipt absence verification failed"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+use tendermint::{
+    account,
+    block::CommitSig,
+    crypto::default::signature::Verifier as SignatureVerifier,
+    validator,
+    vote::{SignedVote, ValidatorIndex, Vote},
+};
+use tendermint_light_client_verifier::{
+    errors::VerificationError,
+    operations::{ProdCommitValidator, VotingPowerCalculator, VotingPowerTally},
+    predicates::ProdPredicates,
+    types::{SignedHeader, TrustThreshold, ValidatorSet},
+    PredicateVerifier,
+};
+
+/// Verifier that checks commit signatures in parallel, otherwise the same as
+/// `ProdVerifier`.
+pub type ParallelVerifier =
+    PredicateVerifier<ProdPredicates, ParallelVotingPowerCalculator, ProdCommitValidator>;
+
+/// Voting power calculator that verifies all the signatures it needs at once on
+/// the rayon thread pool, instead of one at a time until the threshold is met.
+///
+/// Unlike `ProdVotingPowerCalculator`, any invalid signature of a validator of
+/// the checked sets is an error, even if the others already have enough power.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParallelVotingPowerCalculator;
+
+impl ParallelVotingPowerCalculator {
+    /// Addresses of the validators of `sets` with a valid commit signature.
+    // The error type is the one `VotingPowerCalculator` returns.
+    #[allow(clippy::result_large_err)]
+    fn signers(
+        signed_header: &SignedHeader,
+        sets: &[&ValidatorSet],
+    ) -> Result<HashSet<account::Id>, VerificationError> {
+        let mut votes = HashMap::new();
+        for (idx, commit_sig) in signed_header.commit.signatures.iter().enumerate() {
+            let CommitSig::BlockIdFlagCommit {
+                validator_address,
+                timestamp,
+                signature,
+            } = commit_sig
+            else {
+                continue;
+            };
+            let vote = Vote {
+                vote_type: tendermint::vote::Type::Precommit,
+                height: signed_header.commit.height,
+                round: signed_header.commit.round,
+                block_id: Some(signed_header.commit.block_id),
+                timestamp: Some(*timestamp),
+                validator_address: *validator_address,
+                // We never have more than 2³¹ signatures so this always succeeds.
+                validator_index: ValidatorIndex::try_from(idx).expect("Never fails"),
+                signature: signature.clone(),
+                extension: Default::default(),
+                extension_signature: None,
+            };
+            let signed_vote = SignedVote::from_vote(vote, signed_header.header.chain_id.clone())
+                .ok_or_else(VerificationError::missing_signature)?;
+            if votes.insert(*validator_address, signed_vote).is_some() {
+                return Err(VerificationError::duplicate_validator(*validator_address));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let to_verify: Vec<(&validator::Info, &SignedVote)> = sets
+            .iter()
+            .flat_map(|set| set.validators())
+            .filter(|validator| seen.insert(validator.address))
+            .filter_map(|validator| votes.get(&validator.address).map(|vote| (validator, vote)))
+            .collect();
+
+        to_verify
+            .par_iter()
+            .map(|(validator, vote)| {
+                let sign_bytes = vote.sign_bytes();
+                validator
+                    .verify_signature::<SignatureVerifier>(&sign_bytes, vote.signature())
+                    .map(|_| validator.address)
+                    .map_err(|_| {
+                        VerificationError::invalid_signature(
+                            vote.signature().as_bytes().to_vec(),
+                            Box::new((*validator).clone()),
+                            sign_bytes,
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    fn tally(
+        &self,
+        signers: &HashSet<account::Id>,
+        validator_set: &ValidatorSet,
+        trust_threshold: TrustThreshold,
+    ) -> VotingPowerTally {
+        let tallied = validator_set
+            .validators()
+            .iter()
+            .filter(|validator| signers.contains(&validator.address))
+            .map(|validator| validator.power())
+            .sum();
+        VotingPowerTally {
+            total: self.total_power_of(validator_set),
+            tallied,
+            trust_threshold,
+        }
+    }
+}
+
+impl VotingPowerCalculator for ParallelVotingPowerCalculator {
+    fn voting_power_in(
+        &self,
+        signed_header: &SignedHeader,
+        validator_set: &ValidatorSet,
+        trust_threshold: TrustThreshold,
+    ) -> Result<VotingPowerTally, VerificationError> {
+        let signers = Self::signers(signed_header, &[validator_set])?;
+        Ok(self.tally(&signers, validator_set, trust_threshold))
+    }
+
+    fn voting_power_in_sets(
+        &self,
+        signed_header: &SignedHeader,
+        first_set: (&ValidatorSet, TrustThreshold),
+        second_set: (&ValidatorSet, TrustThreshold),
+    ) -> Result<(VotingPowerTally, VotingPowerTally), VerificationError> {
+        let signers = Self::signers(signed_header, &[first_set.0, second_set.0])?;
+        Ok((
+            self.tally(&signers, first_set.0, first_set.1),
+            self.tally(&signers, second_set.0, second_set.1),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint::{Signature, Time};
+    use tendermint_light_client_verifier::operations::ProdVotingPowerCalculator;
+    use tendermint_testgen::{
+        light_block::TmLightBlock, Commit, Generator, Header, LightBlock, Validator,
+    };
+
+    use crate::client_options::ClientOptions;
+
+    /// Trust threshold of a client with the default parameters.
+    fn trust_threshold() -> TrustThreshold {
+        ClientOptions::default().trust_threshold.try_into().unwrap()
+    }
+
+    fn light_block(validators: usize) -> TmLightBlock {
+        let validators: Vec<Validator> = (0..validators)
+            .map(|i| Validator::new(&format!("v{i}")).voting_power(10))
+            .collect();
+        let header = Header::new(&validators)
+            .chain_id("parallel-0")
+            .height(2)
+            .time(Time::from_unix_timestamp(1_700_000_000, 0).unwrap());
+        LightBlock::new(header.clone(), Commit::new(header, 1))
+            .generate()
+            .unwrap()
+    }
+
+    #[test]
+    fn tallies_all_signers() {
+        let block = light_block(20);
+        let (first, second) = ParallelVotingPowerCalculator
+            .voting_power_in_sets(
+                &block.signed_header,
+                (&block.validators, trust_threshold()),
+                (&block.validators, TrustThreshold::TWO_THIRDS),
+            )
+            .unwrap();
+        assert_eq!((first.tallied, first.total), (200, 200));
+        assert_eq!(second.trust_threshold, TrustThreshold::TWO_THIRDS);
+
+        // the sequential calculator stops counting at the threshold
+        let prod = ProdVotingPowerCalculator::default()
+            .voting_power_in(&block.signed_header, &block.validators, trust_threshold())
+            .unwrap();
+        assert!(prod.tallied < first.tallied);
+    }
+
+    #[test]
+    fn rejects_invalid_signature() {
+        let mut block = light_block(20);
+        let Some(CommitSig::BlockIdFlagCommit {
+            signature: Some(signature),
+            ..
+        }) = block.signed_header.commit.signatures.last_mut()
+        else {
+            panic!("last validator signed the commit");
+        };
+        let mut bytes = signature.as_bytes().to_vec();
+        bytes[0] ^= 0xff;
+        *signature = Signature::new(bytes).unwrap().unwrap();
+
+        let err = ParallelVotingPowerCalculator
+            .voting_power_in(&block.signed_header, &block.validators, trust_threshold())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("failed to verify signature"),
+            "{err}"
+        );
+    }
+}
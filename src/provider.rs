@@ -1,7 +1,51 @@
+use std::error::Error;
+
 use ibc_client_tendermint::types::{ConsensusState as ConsensusStateType, Header};
-use ibc_core::{client::types::Height, commitment_types::commitment::CommitmentRoot};
-use tendermint::{account::Id, block::signed_header::SignedHeader};
-use tendermint_rpc::{Client, HttpClient, Paging, Url};
+use ibc_core::{
+    client::types::Height,
+    commitment_types::commitment::{CommitmentProofBytes, CommitmentRoot},
+    host::types::{identifiers::ChainId, path::Path},
+};
+use ibc_proto::{ibc::core::commitment::v1::MerkleProof, ics23::CommitmentProof};
+use prost::Message;
+use tendermint::{
+    account::Id, block::signed_header::SignedHeader, merkle::proof::ProofOps, validator, Hash,
+};
+use tendermint_rpc::{
+    endpoint::{abci_query::AbciQuery, block, block_results, tx},
+    Client, HttpClient, Paging, Url,
+};
+use tracing::instrument;
+
+use crate::light_client::LightClient;
+
+/// Store path queried for IBC keys, the IBC store of the Cosmos SDK multistore.
+pub const IBC_QUERY_PATH: &str = "store/ibc/key";
+
+/// Value of an IBC path and its ICS-23 proof.
+///
+/// The proof is against the app hash of the block at `height + 1`, because a
+/// block header commits to the state after the previous block.
+pub struct IbcQueryResponse {
+    /// Stored value, empty if the path is not set.
+    pub value: Vec<u8>,
+    pub proof: CommitmentProofBytes,
+    pub height: u64,
+}
+
+/// Converts the `ProofOps` of an `abci_query` into the `MerkleProof` proof bytes
+/// IBC verifies: every op carries one ICS-23 `CommitmentProof`, from the IAVL
+/// store up to the multistore root.
+pub fn proof_ops_to_commitment_proof(
+    proof_ops: &ProofOps,
+) -> Result<CommitmentProofBytes, Box<dyn Error>> {
+    let proofs = proof_ops
+        .ops
+        .iter()
+        .map(|op| CommitmentProof::decode(op.data.as_slice()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CommitmentProofBytes::try_from(MerkleProof { proofs })?)
+}
 
 /// Provider help use query data from chain.
 /// We use it for test only. However good to have this API here.
@@ -18,6 +62,7 @@ impl LightClientProvider {
         }
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub async fn consensus_state(&self, height: u32) -> ConsensusStateType {
         let block = self.provider.block(height).await.unwrap();
 
@@ -32,6 +77,7 @@ impl LightClientProvider {
         }
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub async fn light_header(&self, height: u32) -> Header {
         let signed_header = self.get_signed_header(height).await;
 
@@ -45,16 +91,156 @@ impl LightClientProvider {
         }
     }
 
-    pub async fn latest_height(&self) -> u64 {
-        let block = self.provider.latest_block_results().await;
-        block.unwrap().height.into()
+    /// Header at `height` for a client that trusts `trusted_height`, with the
+    /// validator set the trusted block named as next.
+    #[instrument(level = "debug", skip(self), fields(%trusted_height), err)]
+    pub async fn header_trusting(
+        &self,
+        height: u32,
+        trusted_height: Height,
+    ) -> Result<Header, Box<dyn Error>> {
+        let signed_header = self.provider.commit(height).await?.signed_header;
+        let trusted_next = u32::try_from(trusted_height.revision_height() + 1)?;
+        let validators = self.provider.validators(height, Paging::All).await?;
+        let trusted_next_validators = self.provider.validators(trusted_next, Paging::All).await?;
+
+        Ok(Header {
+            validator_set: tendermint::validator::Set::with_proposer(
+                validators.validators,
+                signed_header.header.proposer_address,
+            )?,
+            signed_header,
+            trusted_height,
+            trusted_next_validator_set: tendermint::validator::Set::without_proposer(
+                trusted_next_validators.validators,
+            ),
+        })
+    }
+
+    /// Updates `client` to the header at `target` by bisection: when a header
+    /// does not verify, the one halfway to the trusted height is tried, and
+    /// after every verified header the target again. Returns the heights the
+    /// client was updated to.
+    #[instrument(level = "debug", skip(self, client), err)]
+    pub async fn bisect(
+        &self,
+        client: &mut LightClient,
+        target: u64,
+    ) -> Result<Vec<Height>, Box<dyn Error>> {
+        let mut verified = vec![];
+        let mut pivot = target;
+        loop {
+            client.status()?.verify_is_active()?;
+            let trusted = client.client_state()?.inner().latest_height;
+            if trusted.revision_height() >= target {
+                return Ok(verified);
+            }
+            let header = self.header_trusting(pivot.try_into()?, trusted).await?;
+            match client.update(header) {
+                Ok(height) => {
+                    verified.push(height);
+                    pivot = target;
+                }
+                Err(e) if pivot == trusted.revision_height() + 1 => {
+                    return Err(format!("no header after {trusted} verifies: {e}").into());
+                }
+                Err(_) => pivot = (trusted.revision_height() + pivot) / 2,
+            }
+        }
+    }
+
+    /// Chain id the node reports in its status.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn chain_id(&self) -> Result<ChainId, Box<dyn Error>> {
+        let status = self.provider.status().await?;
+        Ok(ChainId::new(status.node_info.network.as_str())?)
+    }
+
+    /// Block at `height`, or the latest block.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn block(&self, height: Option<u64>) -> Result<block::Response, Box<dyn Error>> {
+        Ok(match height {
+            Some(height) => self.provider.block(u32::try_from(height)?).await?,
+            None => self.provider.latest_block().await?,
+        })
+    }
+
+    /// Every validator of the set at `height`.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn validators(&self, height: u64) -> Result<Vec<validator::Info>, Box<dyn Error>> {
+        let response = self
+            .provider
+            .validators(u32::try_from(height)?, Paging::All)
+            .await?;
+        Ok(response.validators)
+    }
+
+    /// Queries `data` at `path` and `height` with `prove=true`.
+    #[instrument(level = "debug", skip(self, data), fields(data = %hex::encode(&data)), err)]
+    pub async fn abci_query(
+        &self,
+        path: &str,
+        data: Vec<u8>,
+        height: u64,
+    ) -> Result<AbciQuery, Box<dyn Error>> {
+        Ok(self
+            .provider
+            .abci_query(Some(path.to_string()), data, Some(height.try_into()?), true)
+            .await?)
+    }
+
+    /// Results of the transactions and events of the block at `height`.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn block_results(
+        &self,
+        height: u64,
+    ) -> Result<block_results::Response, Box<dyn Error>> {
+        Ok(self.provider.block_results(u32::try_from(height)?).await?)
+    }
+
+    /// Transaction `hash` with its inclusion proof, fetched with `prove=true`.
+    #[instrument(level = "debug", skip(self), fields(%hash), err)]
+    pub async fn tx(&self, hash: Hash) -> Result<tx::Response, Box<dyn Error>> {
+        Ok(self.provider.tx(hash, true).await?)
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn latest_height(&self) -> Result<u64, Box<dyn Error>> {
+        let block = self.provider.latest_block_results().await?;
+        Ok(block.height.into())
+    }
+
+    /// Queries `path` in the IBC store at `height` with `prove=true`.
+    #[instrument(level = "debug", skip(self), fields(%path), err)]
+    pub async fn query_ibc_proof(
+        &self,
+        path: &Path,
+        height: u64,
+    ) -> Result<IbcQueryResponse, Box<dyn Error>> {
+        let response = self
+            .abci_query(IBC_QUERY_PATH, path.to_string().into_bytes(), height)
+            .await?;
+        if response.code.is_err() {
+            return Err(format!("abci_query {path} failed: {}", response.log).into());
+        }
+        let proof_ops = response
+            .proof
+            .ok_or_else(|| format!("abci_query {path} returned no proof"))?;
+
+        Ok(IbcQueryResponse {
+            value: response.value,
+            proof: proof_ops_to_commitment_proof(&proof_ops)?,
+            height: response.height.value(),
+        })
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub async fn get_signed_header(&self, height: u32) -> SignedHeader {
         let commit = self.provider.commit(height).await;
         commit.unwrap().signed_header
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub async fn get_validator_set(
         &self,
         height: u32,
@@ -0,0 +1,627 @@
+use std::{collections::BTreeMap, error::Error, sync::Arc};
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState, Header};
+use ibc_core::{
+    client::types::Height,
+    commitment_types::{
+        commitment::CommitmentRoot,
+        merkle::MerkleProof,
+        proto::{ics23::HostFunctionsManager, v1::MerklePath},
+        specs::ProofSpecs,
+    },
+};
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use prost::Message;
+use serde_json::{json, Value};
+use tendermint::{
+    block::{self, Block},
+    crypto::default::Sha256,
+    evidence::{self, Evidence},
+    merkle, validator, Hash,
+};
+use tendermint_proto::{
+    types::{
+        CommitSig as RawCommitSig, DuplicateVoteEvidence as RawDuplicateVoteEvidence,
+        LightClientAttackEvidence as RawLightClientAttackEvidence,
+    },
+    Protobuf,
+};
+use tendermint_rpc::endpoint::{abci_query::AbciQuery, commit, validators};
+use tokio::{net::TcpListener, sync::Mutex};
+
+use crate::{
+    batch::BatchVerifier,
+    inclusion::data_hash,
+    metrics::Metrics,
+    mock_rpc::{param_u64, rpc_response, to_value, RpcRequest},
+    provider::{proof_ops_to_commitment_proof, LightClientProvider},
+};
+
+/// Headers the proxy trusts: the initial trusted height and every header
+/// verified from it since, all installed in the client of `verifier`.
+struct Trusted {
+    trusted_height: Height,
+    headers: BTreeMap<u64, Header>,
+    verifier: BatchVerifier,
+}
+
+/// Tendermint RPC proxy that forwards `block`, `commit`, `validators` and
+/// `abci_query` calls to a full node and only answers with responses it could
+/// verify against headers trusted by the light client.
+pub struct VerifyingProxy {
+    provider: LightClientProvider,
+    trusted: Mutex<Trusted>,
+    metrics: Metrics,
+}
+
+impl VerifyingProxy {
+    /// Proxy for the node behind `provider`, trusting `cs` at `trusted_height`.
+    /// Headers of another chain than the one of `client_state` are rejected.
+    pub fn new(
+        provider: LightClientProvider,
+        client_state: ClientStateType,
+        cs: ConsensusState,
+        trusted_height: Height,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut client_state = client_state;
+        client_state.latest_height = trusted_height;
+        let metrics = Metrics::default();
+        let verifier = BatchVerifier::new(client_state, cs, None)?.with_metrics(metrics.clone());
+
+        Ok(Self {
+            provider,
+            trusted: Mutex::new(Trusted {
+                trusted_height,
+                headers: BTreeMap::new(),
+                verifier,
+            }),
+            metrics,
+        })
+    }
+
+    /// Passes through the `result` of a request to the node, counting it in the metrics.
+    fn rpc<T>(&self, method: &str, result: Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+        self.metrics.observe_rpc(method, &result);
+        result
+    }
+
+    /// Header at `height`, verified from the closest trusted height below it.
+    async fn verified_header(&self, height: u64) -> Result<Header, Box<dyn Error>> {
+        let mut trusted = self.trusted.lock().await;
+        if let Some(header) = trusted.headers.get(&height) {
+            return Ok(header.clone());
+        }
+        let revision = trusted.trusted_height.revision_number();
+        let trusted_height = trusted
+            .headers
+            .range(..height)
+            .next_back()
+            .map(|(&h, _)| h)
+            .unwrap_or(trusted.trusted_height.revision_height());
+        if height <= trusted_height {
+            return Err(format!(
+                "height {height} is not above the trusted height {}",
+                trusted.trusted_height
+            )
+            .into());
+        }
+
+        let header = self.rpc(
+            "header",
+            self.provider
+                .header_trusting(height.try_into()?, Height::new(revision, trusted_height)?)
+                .await,
+        )?;
+        trusted
+            .verifier
+            .apply(&header)
+            .map_err(|e| format!("header at height {height} is invalid: {e}"))?;
+        trusted.headers.insert(height, header.clone());
+        Ok(header)
+    }
+
+    async fn latest_height(&self) -> Result<u64, Box<dyn Error>> {
+        let response = self.rpc("block", self.provider.block(None).await)?;
+        let height = response.block.header.height.value();
+        self.metrics.observe_chain_head(height);
+        Ok(height)
+    }
+
+    /// The block must hash to the verified header at its height, and its
+    /// transactions, last commit and evidence to the hashes in that header.
+    async fn block(&self, height: Option<u64>) -> Result<Value, Box<dyn Error>> {
+        let mut response = self.rpc("block", self.provider.block(height).await)?;
+        let block_height = response.block.header.height.value();
+        if let Some(height) = height.filter(|h| *h != block_height) {
+            return Err(format!("node answered block {block_height} instead of {height}").into());
+        }
+        let header = self
+            .verified_header(block_height)
+            .await?
+            .signed_header
+            .header;
+        let hash = header.hash();
+
+        if response.block_id.hash != hash || response.block.header.hash() != hash {
+            return Err(format!(
+                "block at height {block_height} does not match the verified header"
+            )
+            .into());
+        }
+        verify_block_contents(&mut response.block, &header)
+            .map_err(|e| format!("block at height {block_height} is invalid: {e}"))?;
+        Ok(to_value(response)?)
+    }
+
+    /// The commit is the one the verified header was checked with.
+    async fn commit(&self, height: Option<u64>) -> Result<Value, Box<dyn Error>> {
+        let height = match height {
+            Some(height) => height,
+            None => self.latest_height().await?,
+        };
+        let header = self.verified_header(height).await?;
+        Ok(to_value(commit::Response {
+            signed_header: header.signed_header,
+            canonical: true,
+        })?)
+    }
+
+    /// The validator set must hash to `validators_hash` of the verified header.
+    async fn validators(
+        &self,
+        height: Option<u64>,
+        page: usize,
+        per_page: usize,
+    ) -> Result<Value, Box<dyn Error>> {
+        let height = match height {
+            Some(height) => height,
+            None => self.latest_height().await?,
+        };
+        let all = self.rpc("validators", self.provider.validators(height).await)?;
+        let header = self.verified_header(height).await?;
+        if validator::Set::without_proposer(all.clone()).hash()
+            != header.signed_header.header.validators_hash
+        {
+            return Err(
+                format!("validators at height {height} do not match the verified header").into(),
+            );
+        }
+
+        let total = all.len() as i32;
+        let skip = page
+            .saturating_sub(1)
+            .checked_mul(per_page)
+            .ok_or_else(|| format!("page {page} of {per_page} validators is out of range"))?;
+        let page = all.into_iter().skip(skip).take(per_page).collect();
+        Ok(to_value(validators::Response::new(
+            header.signed_header.header.height,
+            page,
+            total,
+        ))?)
+    }
+
+    /// The proof of a `store/<name>/key` query must prove the returned value,
+    /// or its absence, against the app hash of the verified header at the next
+    /// height.
+    async fn abci_query(
+        &self,
+        path: &str,
+        data: Vec<u8>,
+        height: Option<u64>,
+    ) -> Result<Value, Box<dyn Error>> {
+        let store = path
+            .strip_prefix("store/")
+            .and_then(|p| p.strip_suffix("/key"))
+            .ok_or_else(|| format!("only store/<name>/key queries can be verified, got {path}"))?;
+        let key = String::from_utf8(data.clone())
+            .map_err(|_| "only UTF-8 keys can be verified".to_string())?;
+        // the latest block does not commit to its own state yet
+        let height = match height {
+            Some(height) => height,
+            None => self
+                .latest_height()
+                .await?
+                .checked_sub(1)
+                .ok_or("the chain has no committed state yet")?,
+        };
+
+        let response = self.rpc(
+            "abci_query",
+            self.provider.abci_query(path, data, height).await,
+        )?;
+        if response.code.is_err() {
+            return Err(format!("abci_query {path} failed: {}", response.log).into());
+        }
+        if response.height.value() != height {
+            return Err(format!(
+                "node answered abci_query at height {} instead of {height}",
+                response.height
+            )
+            .into());
+        }
+        let proof_height = response.height.value() + 1;
+        let header = self.verified_header(proof_height).await?;
+        verify_query_proof(&response, store, key, &header).map_err(|e| {
+            format!("abci_query proof against height {proof_height} is invalid: {e}")
+        })?;
+
+        Ok(json!({ "response": response }))
+    }
+
+    async fn handle(&self, method: &str, params: &Value) -> Result<Value, String> {
+        let height = param_u64(params, "height").filter(|h| *h != 0);
+        let result = match method {
+            "block" => self.block(height).await,
+            "commit" => self.commit(height).await,
+            "validators" => {
+                self.validators(
+                    height,
+                    param_u64(params, "page").unwrap_or(1) as usize,
+                    param_u64(params, "per_page").unwrap_or(30) as usize,
+                )
+                .await
+            }
+            "abci_query" => {
+                let path = params
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let data = params
+                    .get("data")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                match hex::decode(data) {
+                    Ok(data) => self.abci_query(path, data, height).await,
+                    Err(e) => Err(e.into()),
+                }
+            }
+            other => Err(format!("method {other} is not served by the proxy").into()),
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// Checks the contents of `block` against the hashes `header` commits to.
+/// Contents the header has no hash for are dropped.
+fn verify_block_contents(block: &mut Block, header: &block::Header) -> Result<(), String> {
+    match header.data_hash {
+        Some(hash) if data_hash(&block.data) != hash => {
+            return Err("transactions do not match the data hash".into());
+        }
+        Some(_) => {}
+        None => block.data.clear(),
+    }
+
+    match header.last_commit_hash {
+        Some(hash) => {
+            let signatures = block
+                .last_commit
+                .iter()
+                .flat_map(|commit| commit.signatures.iter().cloned())
+                .map(|sig| RawCommitSig::from(sig).encode_to_vec());
+            if merkle_hash(signatures) != hash {
+                return Err("last commit does not match the last commit hash".into());
+            }
+        }
+        None => block.last_commit = None,
+    }
+
+    match header.evidence_hash {
+        Some(hash) => {
+            let evidence = block.evidence.iter().map(|evidence| match evidence {
+                Evidence::DuplicateVote(e) => {
+                    Protobuf::<RawDuplicateVoteEvidence>::encode_vec(e.as_ref().clone())
+                }
+                Evidence::LightClientAttack(e) => {
+                    Protobuf::<RawLightClientAttackEvidence>::encode_vec(e.as_ref().clone())
+                }
+            });
+            if merkle_hash(evidence) != hash {
+                return Err("evidence does not match the evidence hash".into());
+            }
+        }
+        None => block.evidence = evidence::List::default(),
+    }
+    Ok(())
+}
+
+fn merkle_hash(leaves: impl Iterator<Item = Vec<u8>>) -> Hash {
+    let leaves: Vec<Vec<u8>> = leaves.collect();
+    Hash::Sha256(merkle::simple_hash_from_byte_vectors::<Sha256>(&leaves))
+}
+
+fn verify_query_proof(
+    response: &AbciQuery,
+    store: &str,
+    key: String,
+    header: &Header,
+) -> Result<(), Box<dyn Error>> {
+    let proof_ops = response.proof.as_ref().ok_or("response has no proof")?;
+    let proof_bytes = proof_ops_to_commitment_proof(proof_ops)?;
+    let proof: MerkleProof = RawMerkleProof::decode(proof_bytes.as_ref())?.try_into()?;
+
+    let root = CommitmentRoot::from_bytes(header.signed_header.header.app_hash.as_bytes()).into();
+    let keys = MerklePath {
+        key_path: vec![store.to_string(), key],
+    };
+    let specs = ProofSpecs::cosmos();
+    if response.value.is_empty() {
+        proof.verify_non_membership::<HostFunctionsManager>(&specs, root, keys)?;
+    } else {
+        proof.verify_membership::<HostFunctionsManager>(
+            &specs,
+            root,
+            keys,
+            response.value.clone(),
+            0,
+        )?;
+    }
+    Ok(())
+}
+
+async fn metrics(State(proxy): State<Arc<VerifyingProxy>>) -> String {
+    proxy
+        .metrics
+        .observe_client(&proxy.trusted.lock().await.verifier);
+    proxy.metrics.encode()
+}
+
+async fn handle(
+    State(proxy): State<Arc<VerifyingProxy>>,
+    Json(req): Json<RpcRequest>,
+) -> Json<Value> {
+    let result = proxy.handle(&req.method, &req.params).await;
+    rpc_response(req.id, result)
+}
+
+/// Router answering Tendermint JSON-RPC requests through `proxy`, and
+/// Prometheus metrics on `GET /metrics`.
+pub fn router(proxy: VerifyingProxy) -> Router {
+    Router::new()
+        .route("/", post(handle))
+        .route("/metrics", get(metrics))
+        .with_state(Arc::new(proxy))
+}
+
+/// Serves `proxy` on `listener` until the task is dropped.
+pub async fn serve(listener: TcpListener, proxy: VerifyingProxy) -> std::io::Result<()> {
+    axum::serve(listener, router(proxy)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use ibc_core::{
+        channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+        primitives::Timestamp,
+    };
+    use tendermint::{AppHash, Time};
+    use tendermint_rpc::{Client, HttpClient, Paging};
+
+    use crate::{
+        mock_rpc::{AbciQueryEntry, MockChain},
+        provider::IBC_QUERY_PATH,
+        test_utils::{chain_client_state, get_header, osmosis_proof, spawn_mock_rpc},
+        testgen::{ChainSpec, TestChain},
+    };
+
+    fn commitment() -> Vec<u8> {
+        compute_packet_commitment(
+            &osmosis_proof().data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &Timestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec()
+    }
+
+    // A generated chain whose block 6 carries the app hash of the Osmosis proof,
+    // served by a node that answers the packet commitment query at height 5
+    // with `value`, behind a proxy trusting height 2. Block 4 carries two
+    // transactions.
+    async fn proxy(
+        value: Vec<u8>,
+        tamper: impl FnOnce(&mut MockChain),
+    ) -> (TestChain, HttpClient, String) {
+        let osmosis = osmosis_proof();
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            app_hashes: BTreeMap::from([(6, hex::encode_upper(&osmosis.root))]),
+            txs: BTreeMap::from([(4, vec![hex::encode("tx-1"), hex::encode("tx-2")])]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+
+        let mut mock = MockChain::from(&chain);
+        mock.abci_queries.push(AbciQueryEntry {
+            path: IBC_QUERY_PATH.to_string(),
+            data: osmosis.path().to_string().into_bytes(),
+            response: AbciQuery {
+                value,
+                proof: Some(osmosis.proof_ops()),
+                height: 5u32.into(),
+                ..Default::default()
+            },
+        });
+        tamper(&mut mock);
+        let node = spawn_mock_rpc(mock).await;
+
+        let proxy = VerifyingProxy::new(
+            LightClientProvider::new(node.parse().unwrap()),
+            chain_client_state(&chain),
+            chain.consensus_state(2).unwrap(),
+            chain.ibc_height(2).unwrap(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, proxy));
+        (chain, HttpClient::new(url.as_str()).unwrap(), url)
+    }
+
+    async fn query_commitment(client: &HttpClient) -> Result<AbciQuery, tendermint_rpc::Error> {
+        client
+            .abci_query(
+                Some(IBC_QUERY_PATH.to_string()),
+                osmosis_proof().path().to_string(),
+                Some(5u32.into()),
+                false,
+            )
+            .await
+    }
+
+    #[test]
+    fn checks_block_contents() {
+        // a recorded header of an empty block without evidence
+        let signed_header = get_header().signed_header;
+        let header = signed_header.header;
+        let block = |data, last_commit| {
+            Block::new_unchecked(header.clone(), data, Default::default(), last_commit)
+        };
+
+        let err = verify_block_contents(&mut block(vec![b"tx".to_vec()], None), &header);
+        assert_eq!(err.unwrap_err(), "transactions do not match the data hash");
+        // the commit of the header itself, not of the block before it
+        let err = verify_block_contents(&mut block(vec![], Some(signed_header.commit)), &header);
+        assert_eq!(
+            err.unwrap_err(),
+            "last commit does not match the last commit hash"
+        );
+
+        let mut unhashed = header.clone();
+        unhashed.last_commit_hash = None;
+        let mut stripped = block(vec![], Some(Default::default()));
+        verify_block_contents(&mut stripped, &unhashed).unwrap();
+        assert!(stripped.last_commit.is_none());
+    }
+
+    #[tokio::test]
+    async fn serves_verified_responses() {
+        let (chain, client, _) = proxy(commitment(), |_| {}).await;
+
+        let block = client.block(4u32).await.unwrap();
+        assert_eq!(
+            block.block_id.hash,
+            chain.light_block(4).unwrap().signed_header.header.hash()
+        );
+        assert_eq!(block.block.data, vec![b"tx-1".to_vec(), b"tx-2".to_vec()]);
+        // generated headers do not commit to their last commit
+        assert!(block.block.last_commit.is_none());
+        let commit = client.commit(7u32).await.unwrap();
+        assert_eq!(
+            commit.signed_header,
+            chain.light_block(7).unwrap().signed_header
+        );
+        let validators = client.validators(5u32, Paging::All).await.unwrap();
+        assert_eq!(validators.validators.len(), 4);
+
+        let response = query_commitment(&client).await.unwrap();
+        assert_eq!(response.value, commitment());
+
+        let err = client.block(2u32).await.unwrap_err();
+        assert!(
+            err.to_string().contains("not above the trusted height"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unverifiable_responses() {
+        let (_, client, _) = proxy(b"forged".to_vec(), |mock| {
+            // block 8 is changed after it was signed
+            mock.blocks[7].signed_header.header.app_hash = AppHash::try_from(vec![1; 32]).unwrap();
+            mock.txs.insert(4, vec![hex::encode("forged")]);
+        })
+        .await;
+
+        let err = query_commitment(&client).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("proof against height 6 is invalid"),
+            "{err}"
+        );
+
+        let err = client.block(4u32).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("transactions do not match the data hash"),
+            "{err}"
+        );
+
+        let err = client.block(8u32).await.unwrap_err();
+        assert!(
+            err.to_string().contains("header at height 8 is invalid"),
+            "{err}"
+        );
+
+        let err = client
+            .abci_query(Some("/app/version".to_string()), vec![], None, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("can be verified"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn rejects_query_answered_at_other_height() {
+        let (_, client, _) = proxy(commitment(), |_| {}).await;
+
+        // the node answers with the value and proof recorded at height 5
+        let err = client
+            .abci_query(
+                Some(IBC_QUERY_PATH.to_string()),
+                osmosis_proof().path().to_string(),
+                Some(4u32.into()),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("node answered abci_query at height 5 instead of 4"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_metrics() {
+        let (_, client, url) = proxy(commitment(), |_| {}).await;
+        client.commit(7u32).await.unwrap();
+        // at the latest height, so the proxy asks the node for the chain head,
+        // then rejects the answer recorded at height 5 instead of 9
+        let err = client
+            .abci_query(
+                Some(IBC_QUERY_PATH.to_string()),
+                osmosis_proof().path().to_string(),
+                None,
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("instead of 9"), "{err}");
+
+        let metrics = reqwest::get(format!("{url}/metrics"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(
+            metrics.contains("lightclient_trusted_height 7"),
+            "{metrics}"
+        );
+        assert!(
+            metrics.contains("lightclient_chain_head_lag 3"),
+            "{metrics}"
+        );
+        assert!(metrics.contains(r#"lightclient_rpc_requests_total{method="header"} 1"#));
+        assert!(metrics.contains(r#"lightclient_rpc_requests_total{method="abci_query"} 1"#));
+    }
+}
@@ -0,0 +1,535 @@
+use std::error::Error;
+
+use ibc_client_tendermint::{
+    client_state::ClientState,
+    types::{ClientState as ClientStateType, ConsensusState, Header},
+};
+use ibc_core::{
+    client::{context::client_state::ClientStateCommon, types::Height},
+    commitment_types::commitment::{CommitmentPrefix, CommitmentRoot},
+    host::types::{identifiers::ClientId, path::Path},
+};
+use serde::Serialize;
+use tendermint::{
+    abci::{types::ExecTxResult, Code},
+    serializers, Hash,
+};
+
+use crate::{
+    client_message::{check_chain_id, verify_client_message, TmClientMessage},
+    context::Ctx,
+    inclusion::tx_hash,
+    light_client::LightClient,
+    provider::LightClientProvider,
+};
+
+/// Value of an IBC path proven against a header the light client verified.
+pub struct VerifiedValue {
+    /// Height of the queried state.
+    pub height: u64,
+    /// Height of the header whose app hash commits to the state.
+    pub proof_height: Height,
+    /// Stored value, empty if the path is proven absent.
+    pub value: Vec<u8>,
+}
+
+/// Fetches `path` at `height` with its proof, updates a client trusting
+/// `trusted_cs` at `trusted_height` to `height + 1`, whose app hash commits to
+/// the state at `height`, and verifies the value, or its absence if the node
+/// returns an empty value, against that app hash.
+pub async fn query_verified(
+    provider: &LightClientProvider,
+    client_state: ClientStateType,
+    trusted_cs: &ConsensusState,
+    trusted_height: Height,
+    prefix: &CommitmentPrefix,
+    path: Path,
+    height: u64,
+) -> Result<VerifiedValue, Box<dyn Error>> {
+    let response = provider.query_ibc_proof(&path, height).await?;
+    if response.height != height {
+        return Err(format!(
+            "node answered the query at height {} instead of {height}",
+            response.height
+        )
+        .into());
+    }
+    let next_height = height
+        .checked_add(1)
+        .ok_or_else(|| format!("no header follows height {height}"))?;
+    let proof_height = Height::new(trusted_height.revision_number(), next_height)?;
+
+    let root: CommitmentRoot = if proof_height == trusted_height {
+        trusted_cs.root.clone()
+    } else if proof_height > trusted_height {
+        let header = provider
+            .header_trusting(proof_height.revision_height().try_into()?, trusted_height)
+            .await?;
+        let client_id = ClientId::new("stand-alone", 0)?;
+        let message = TmClientMessage::Header(Box::new(header.clone()));
+        if verify_client_message(
+            client_state.clone(),
+            &client_id,
+            &message,
+            std::slice::from_ref(trusted_cs),
+            None,
+        )? {
+            return Err(format!("header at {proof_height} is a misbehaviour").into());
+        }
+        ConsensusState::from(header).root
+    } else {
+        return Err(format!(
+            "header at {proof_height} is below the trusted height {trusted_height}"
+        )
+        .into());
+    };
+
+    let client = ClientState::from(client_state);
+    if response.value.is_empty() {
+        client
+            .verify_non_membership(prefix, &response.proof, &root, path)
+            .map_err(|e| format!("non-membership verification failed: {e}"))?;
+    } else {
+        client
+            .verify_membership(prefix, &response.proof, &root, path, response.value.clone())
+            .map_err(|e| format!("membership verification failed: {e}"))?;
+    }
+
+    Ok(VerifiedValue {
+        height: response.height,
+        proof_height,
+        value: response.value,
+    })
+}
+
+/// Transaction proven to be in a block the light client verified.
+pub struct VerifiedTx {
+    /// Height of the block that includes the transaction.
+    pub height: Height,
+    /// Position of the transaction in the block.
+    pub index: u32,
+    pub tx: Vec<u8>,
+}
+
+/// Fetches the transaction `hash` with its inclusion proof, updates a client
+/// trusting `trusted_cs` at `trusted_height` to the block that includes it,
+/// and verifies the proof against the `data_hash` of that block.
+pub async fn query_tx_verified(
+    provider: &LightClientProvider,
+    client_state: ClientStateType,
+    trusted_cs: &ConsensusState,
+    trusted_height: Height,
+    hash: Hash,
+) -> Result<VerifiedTx, Box<dyn Error>> {
+    let response = provider.tx(hash).await?;
+    if tx_hash(&response.tx) != hash {
+        return Err(format!("node answered with another tx than {hash}").into());
+    }
+    let proof = response
+        .proof
+        .ok_or_else(|| format!("tx {hash} returned no proof"))?;
+    let height = Height::new(trusted_height.revision_number(), response.height.value())?;
+    if height <= trusted_height {
+        return Err(format!(
+            "tx {hash} at {height} is not above the trusted height {trusted_height}"
+        )
+        .into());
+    }
+    let (client, header) =
+        updated_client(provider, client_state, trusted_cs, trusted_height, height).await?;
+    client
+        .verify_tx_inclusion(header, &response.tx, &proof)
+        .map_err(|e| format!("inclusion verification failed: {e}"))?;
+
+    Ok(VerifiedTx {
+        height,
+        index: response.index,
+        tx: response.tx,
+    })
+}
+
+/// Fields of a transaction result that `last_results_hash` commits to. The
+/// log, info, events and codespace are left out, nothing proves them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CommittedTxResult {
+    pub code: Code,
+    #[serde(with = "serializers::bytes::base64string")]
+    pub data: Vec<u8>,
+    #[serde(with = "serializers::from_str")]
+    pub gas_wanted: i64,
+    #[serde(with = "serializers::from_str")]
+    pub gas_used: i64,
+}
+
+impl From<ExecTxResult> for CommittedTxResult {
+    fn from(result: ExecTxResult) -> Self {
+        Self {
+            code: result.code,
+            data: result.data.to_vec(),
+            gas_wanted: result.gas_wanted,
+            gas_used: result.gas_used,
+        }
+    }
+}
+
+/// Results of the transactions of a block, verified against the
+/// `last_results_hash` of the next header.
+pub struct VerifiedBlockResults {
+    /// Height of the block whose transactions returned the results.
+    pub height: u64,
+    /// Height of the header whose `last_results_hash` commits to the results.
+    pub proof_height: Height,
+    pub results: Vec<CommittedTxResult>,
+}
+
+/// Fetches the results of the transactions of the block at `height`, updates
+/// a client trusting `trusted_cs` at `trusted_height` to the header at
+/// `height + 1`, and verifies the results against its `last_results_hash`.
+pub async fn query_block_results_verified(
+    provider: &LightClientProvider,
+    client_state: ClientStateType,
+    trusted_cs: &ConsensusState,
+    trusted_height: Height,
+    height: u64,
+) -> Result<VerifiedBlockResults, Box<dyn Error>> {
+    let next_height = height
+        .checked_add(1)
+        .ok_or_else(|| format!("no header follows height {height}"))?;
+    let proof_height = Height::new(trusted_height.revision_number(), next_height)?;
+    if proof_height <= trusted_height {
+        return Err(format!(
+            "header at {proof_height} is not above the trusted height {trusted_height}"
+        )
+        .into());
+    }
+    let results = provider
+        .block_results(height)
+        .await?
+        .txs_results
+        .unwrap_or_default();
+    let (client, header) = updated_client(
+        provider,
+        client_state,
+        trusted_cs,
+        trusted_height,
+        proof_height,
+    )
+    .await?;
+    client
+        .verify_block_results(header, &results)
+        .map_err(|e| format!("results verification failed: {e}"))?;
+
+    Ok(VerifiedBlockResults {
+        height,
+        proof_height,
+        results: results.into_iter().map(CommittedTxResult::from).collect(),
+    })
+}
+
+/// Client trusting `trusted_cs` at `trusted_height`, updated to the header at
+/// `height`, and that header.
+async fn updated_client(
+    provider: &LightClientProvider,
+    client_state: ClientStateType,
+    trusted_cs: &ConsensusState,
+    trusted_height: Height,
+    height: Height,
+) -> Result<(LightClient, Header), Box<dyn Error>> {
+    let header = provider
+        .header_trusting(height.revision_height().try_into()?, trusted_height)
+        .await?;
+
+    check_chain_id(&header, &client_state.chain_id)?;
+
+    let mut client_state = client_state;
+    client_state.latest_height = trusted_height;
+    let mut client: LightClient = LightClient::initialise(
+        Ctx::default(),
+        ClientId::new("stand-alone", 0)?,
+        client_state,
+        trusted_cs.clone(),
+    )?;
+    client.update(header.clone())?;
+    Ok((client, header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use axum::{routing::post, Json, Router};
+    use ibc_core::{
+        channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+        primitives::Timestamp,
+    };
+    use serde_json::Value;
+    use tendermint::Time;
+    use tendermint_rpc::endpoint::abci_query::AbciQuery;
+    use tokio::net::TcpListener;
+
+    use crate::{
+        mock_rpc::{rpc_response, AbciQueryEntry, MockChain, RpcRequest},
+        provider::IBC_QUERY_PATH,
+        test_utils::{chain_client_state, osmosis_proof, spawn_mock_rpc},
+        testgen::{ChainSpec, TestChain},
+    };
+
+    // A generated chain whose block 6 carries the app hash of the Osmosis proof,
+    // and a node answering the query of the packet commitment at height 5 with `value`.
+    async fn provider_with_value(value: Vec<u8>) -> (TestChain, LightClientProvider) {
+        let osmosis = osmosis_proof();
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            app_hashes: BTreeMap::from([(6, hex::encode_upper(&osmosis.root))]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+
+        let mut mock = MockChain::from(&chain);
+        mock.abci_queries.push(AbciQueryEntry {
+            path: IBC_QUERY_PATH.to_string(),
+            data: osmosis.path().to_string().into_bytes(),
+            response: AbciQuery {
+                value,
+                proof: Some(osmosis.proof_ops()),
+                height: 5u32.into(),
+                ..Default::default()
+            },
+        });
+        let url = spawn_mock_rpc(mock).await;
+        (chain, LightClientProvider::new(url.parse().unwrap()))
+    }
+
+    fn commitment() -> Vec<u8> {
+        compute_packet_commitment(
+            &osmosis_proof().data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &Timestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec()
+    }
+
+    #[tokio::test]
+    async fn verifies_value_against_next_header() {
+        let (chain, provider) = provider_with_value(commitment()).await;
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+
+        let verified = query_verified(
+            &provider,
+            chain_client_state(&chain),
+            &chain.consensus_state(2).unwrap(),
+            chain.ibc_height(2).unwrap(),
+            &prefix,
+            osmosis_proof().path(),
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(verified.value, commitment());
+        assert_eq!(verified.proof_height, chain.ibc_height(6).unwrap());
+    }
+
+    #[tokio::test]
+    async fn rejects_value_the_proof_does_not_commit_to() {
+        let (chain, provider) = provider_with_value(b"forged".to_vec()).await;
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+
+        let err = query_verified(
+            &provider,
+            chain_client_state(&chain),
+            &chain.consensus_state(2).unwrap(),
+            chain.ibc_height(2).unwrap(),
+            &prefix,
+            osmosis_proof().path(),
+            5,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("membership verification failed"));
+    }
+
+    #[tokio::test]
+    async fn rejects_value_at_other_height() {
+        let (chain, provider) = provider_with_value(commitment()).await;
+        let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+
+        // the node answers with the value at height 5 whatever the height asked
+        let err = query_verified(
+            &provider,
+            chain_client_state(&chain),
+            &chain.consensus_state(2).unwrap(),
+            chain.ibc_height(2).unwrap(),
+            &prefix,
+            osmosis_proof().path(),
+            4,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "node answered the query at height 5 instead of 4"
+        );
+    }
+
+    #[tokio::test]
+    async fn verifies_tx_in_block() {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            txs: BTreeMap::from([
+                (2, vec!["01".to_string()]),
+                (6, vec!["aa".to_string(), "bb".to_string()]),
+            ]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let url = spawn_mock_rpc(MockChain::from(&chain)).await;
+        let provider = LightClientProvider::new(url.parse().unwrap());
+        let trusted_cs = chain.consensus_state(2).unwrap();
+        let query = |tx: &[u8]| {
+            query_tx_verified(
+                &provider,
+                chain_client_state(&chain),
+                &trusted_cs,
+                chain.ibc_height(2).unwrap(),
+                tx_hash(tx),
+            )
+        };
+
+        let verified = query(&[0xbb]).await.unwrap();
+        assert_eq!(verified.height, chain.ibc_height(6).unwrap());
+        assert_eq!(verified.index, 1);
+        assert_eq!(verified.tx, vec![0xbb]);
+
+        let err = query(&[0x01]).await.err().unwrap();
+        assert!(err.to_string().contains("is not above the trusted height"));
+        assert!(query(&[0xcc]).await.is_err());
+    }
+
+    // A node answering every `tx` request with the proven transaction `aa`,
+    // and forwarding the other requests to `node`.
+    async fn spawn_node_answering_aa(node: String) -> String {
+        let aa = LightClientProvider::new(node.parse().unwrap())
+            .tx(tx_hash(&[0xaa]))
+            .await
+            .unwrap();
+        let aa = serde_json::to_value(aa).unwrap();
+        let client = reqwest::Client::new();
+        let handle = move |Json(req): Json<RpcRequest>| async move {
+            if req.method == "tx" {
+                return rpc_response(req.id, Ok(aa));
+            }
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": req.id,
+                "method": req.method,
+                "params": req.params,
+            });
+            let response = client.post(&node).json(&body).send().await.unwrap();
+            Json(response.json::<Value>().await.unwrap())
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let router = Router::new().route("/", post(handle));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn rejects_other_tx() {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            txs: BTreeMap::from([(6, vec!["aa".to_string(), "bb".to_string()])]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let url = spawn_node_answering_aa(spawn_mock_rpc(MockChain::from(&chain)).await).await;
+        let provider = LightClientProvider::new(url.parse().unwrap());
+
+        // `aa` is in the block, but it is not the tx asked for
+        let hash = tx_hash(&[0xbb]);
+        let err = query_tx_verified(
+            &provider,
+            chain_client_state(&chain),
+            &chain.consensus_state(2).unwrap(),
+            chain.ibc_height(2).unwrap(),
+            hash,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!("node answered with another tx than {hash}")
+        );
+    }
+
+    #[tokio::test]
+    async fn verifies_block_results() {
+        let results = vec![
+            ExecTxResult {
+                gas_used: 80,
+                ..Default::default()
+            },
+            ExecTxResult {
+                code: 5.into(),
+                ..Default::default()
+            },
+        ];
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            tx_results: BTreeMap::from([(5, results.clone())]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let mut mock = MockChain::from(&chain);
+        let provider_of = |mock: MockChain| async {
+            let url = spawn_mock_rpc(mock).await;
+            LightClientProvider::new(url.parse().unwrap())
+        };
+        let trusted_cs = chain.consensus_state(2).unwrap();
+        let trusted_height = chain.ibc_height(2).unwrap();
+
+        let provider = provider_of(mock.clone()).await;
+        let verified = query_block_results_verified(
+            &provider,
+            chain_client_state(&chain),
+            &trusted_cs,
+            trusted_height,
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(verified.proof_height, chain.ibc_height(6).unwrap());
+        assert_eq!(
+            verified.results,
+            results
+                .into_iter()
+                .map(CommittedTxResult::from)
+                .collect::<Vec<_>>()
+        );
+
+        // a node reporting a failed transaction as successful
+        mock.tx_results.get_mut(&5).unwrap()[1].code = 0.into();
+        let provider = provider_of(mock).await;
+        let err = query_block_results_verified(
+            &provider,
+            chain_client_state(&chain),
+            &trusted_cs,
+            trusted_height,
+            5,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("results verification failed"));
+    }
+}
@@ -0,0 +1,264 @@
+use std::{error::Error, fmt};
+
+use clap::ValueEnum;
+use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState, Header};
+use ibc_core::client::types::Height;
+use serde::Serialize;
+use tendermint::{trust_threshold::TrustThreshold as _, Time};
+use tendermint_light_client_verifier::{
+    operations::{ProdVotingPowerCalculator, VotingPowerCalculator},
+    types::TrustThreshold,
+};
+
+/// Output format of a [`VerificationReport`].
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Voting power of the validators that signed a header, against a threshold.
+#[derive(Clone, Debug, Serialize)]
+pub struct VotingPowerReport {
+    pub signed: u64,
+    pub total: u64,
+    pub threshold: String,
+    pub enough: bool,
+}
+
+impl VotingPowerReport {
+    fn new(
+        header: &Header,
+        validators: &tendermint::validator::Set,
+        threshold: TrustThreshold,
+    ) -> Option<Self> {
+        // Fails when a signature is invalid, the verification error reports it.
+        let tally = ProdVotingPowerCalculator::default()
+            .voting_power_in(&header.signed_header, validators, threshold)
+            .ok()?;
+        Some(Self {
+            signed: tally.tallied,
+            total: tally.total,
+            threshold: format!("{}/{}", threshold.numerator(), threshold.denominator()),
+            enough: threshold.is_enough_power(tally.tallied, tally.total),
+        })
+    }
+}
+
+/// Every check made when verifying a header against a trusted consensus state.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerificationReport {
+    pub trusted_height: Height,
+    pub target_height: Height,
+    pub trusted_time: Time,
+    pub target_time: Time,
+    pub evaluated_at: Time,
+    /// Chain id of the header matches the client.
+    pub chain_id_match: bool,
+    /// Seconds since the trusted header time.
+    pub elapsed_secs: u64,
+    pub trusting_period_secs: u64,
+    pub within_trusting_period: bool,
+    /// Seconds the target header time is ahead of `evaluated_at`, negative when it is behind.
+    pub clock_drift_secs: i64,
+    pub max_clock_drift_secs: u64,
+    pub within_clock_drift: bool,
+    /// Hash of the header's trusted next validator set matches the trusted consensus state.
+    pub trusted_validators_hash_match: bool,
+    /// Hash of the header's validator set matches its `validators_hash`.
+    pub validators_hash_match: bool,
+    /// Power of the trusted validators that signed the target header.
+    pub trusted_power: Option<VotingPowerReport>,
+    /// Power of the target header's own validators that signed it.
+    pub untrusted_power: Option<VotingPowerReport>,
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+impl VerificationReport {
+    /// Builds the report of verifying `header` on top of `trusted` at time
+    /// `now`. `result` is the outcome of `verify_client_message`.
+    pub fn new<E: fmt::Display>(
+        client_state: &ClientStateType,
+        trusted: &ConsensusState,
+        header: &Header,
+        now: Time,
+        result: &Result<(), E>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let options = client_state.as_light_client_options()?;
+        let target_time = header.signed_header.header.time;
+
+        let elapsed = now.duration_since(trusted.timestamp).unwrap_or_default();
+        let clock_drift_secs = match target_time.duration_since(now) {
+            Ok(ahead) => ahead.as_secs() as i64,
+            Err(_) => {
+                -(now
+                    .duration_since(target_time)
+                    .unwrap_or_default()
+                    .as_secs() as i64)
+            }
+        };
+
+        Ok(Self {
+            trusted_height: header.trusted_height,
+            target_height: header.height(),
+            trusted_time: trusted.timestamp,
+            target_time,
+            evaluated_at: now,
+            chain_id_match: header.signed_header.header.chain_id.as_str()
+                == client_state.chain_id.as_str(),
+            elapsed_secs: elapsed.as_secs(),
+            trusting_period_secs: options.trusting_period.as_secs(),
+            within_trusting_period: elapsed < options.trusting_period,
+            clock_drift_secs,
+            max_clock_drift_secs: options.clock_drift.as_secs(),
+            within_clock_drift: clock_drift_secs < options.clock_drift.as_secs() as i64,
+            trusted_validators_hash_match: header.trusted_next_validator_set.hash()
+                == trusted.next_validators_hash,
+            validators_hash_match: header.validator_set.hash()
+                == header.signed_header.header.validators_hash,
+            trusted_power: VotingPowerReport::new(
+                header,
+                &header.trusted_next_validator_set,
+                options.trust_threshold,
+            ),
+            untrusted_power: VotingPowerReport::new(
+                header,
+                &header.validator_set,
+                TrustThreshold::TWO_THIRDS,
+            ),
+            verified: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String, Box<dyn Error>> {
+        match format {
+            ReportFormat::Table => Ok(self.to_string()),
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+}
+
+fn check(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "FAILED"
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {}", "trusted height", self.trusted_height)?;
+        writeln!(f, "{:<24} {}", "target height", self.target_height)?;
+        writeln!(f, "{:<24} {}", "trusted time", self.trusted_time)?;
+        writeln!(f, "{:<24} {}", "target time", self.target_time)?;
+        writeln!(f, "{:<24} {}", "evaluated at", self.evaluated_at)?;
+        writeln!(f, "{:<24} [{}]", "chain id", check(self.chain_id_match))?;
+        writeln!(
+            f,
+            "{:<24} {}s of {}s [{}]",
+            "trusting period",
+            self.elapsed_secs,
+            self.trusting_period_secs,
+            check(self.within_trusting_period)
+        )?;
+        writeln!(
+            f,
+            "{:<24} {}s of {}s [{}]",
+            "clock drift",
+            self.clock_drift_secs,
+            self.max_clock_drift_secs,
+            check(self.within_clock_drift)
+        )?;
+        writeln!(
+            f,
+            "{:<24} [{}]",
+            "trusted validators hash",
+            check(self.trusted_validators_hash_match)
+        )?;
+        writeln!(
+            f,
+            "{:<24} [{}]",
+            "validators hash",
+            check(self.validators_hash_match)
+        )?;
+        for (name, power) in [
+            ("trusted power", &self.trusted_power),
+            ("untrusted power", &self.untrusted_power),
+        ] {
+            match power {
+                Some(p) => writeln!(
+                    f,
+                    "{:<24} {}/{} signed, needs > {} [{}]",
+                    name,
+                    p.signed,
+                    p.total,
+                    p.threshold,
+                    check(p.enough)
+                )?,
+                None => writeln!(f, "{:<24} unavailable", name)?,
+            }
+        }
+        match &self.error {
+            None => write!(f, "{:<24} verified", "result"),
+            Some(e) => write!(f, "{:<24} {}", "result", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{client_state, dummy_consensus_state, get_header};
+
+    #[test]
+    fn report_of_valid_header() {
+        let now = Time::parse_from_rfc3339("2024-03-10T12:18:59Z").unwrap();
+        let result: Result<(), String> = Ok(());
+        let report = VerificationReport::new(
+            &client_state(),
+            &dummy_consensus_state(),
+            &get_header(),
+            now,
+            &result,
+        )
+        .unwrap();
+
+        assert_eq!(report.trusted_height, Height::new(0, 6).unwrap());
+        assert_eq!(report.target_height, Height::new(0, 274).unwrap());
+        assert!(report.chain_id_match);
+        assert!(report.within_trusting_period);
+        assert!(report.within_clock_drift);
+        assert!(report.trusted_validators_hash_match);
+        assert!(report.validators_hash_match);
+        assert!(report.trusted_power.unwrap().enough);
+        assert!(report.untrusted_power.unwrap().enough);
+        assert!(report.verified);
+    }
+
+    #[test]
+    fn report_of_future_header() {
+        let now = Time::parse_from_rfc3339("2023-03-10T12:00:00Z").unwrap();
+        let result = Err("header from the future");
+        let report = VerificationReport::new(
+            &client_state(),
+            &dummy_consensus_state(),
+            &get_header(),
+            now,
+            &result,
+        )
+        .unwrap();
+
+        assert!(!report.within_clock_drift);
+        assert_eq!(report.clock_drift_secs, 1139);
+        assert!(!report.verified);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["error"], "header from the future");
+    }
+}
@@ -0,0 +1,86 @@
+//! Results of the transactions of a block against the `last_results_hash` of
+//! the next header, ported from CometBFT's `types/results.go`.
+//!
+//! The hash only commits to the code, data and gas of each result. Their log,
+//! info, events and codespace are left out by CometBFT and cannot be verified.
+
+use alloc::{format, vec::Vec};
+
+use ibc_core::{client::types::error::ClientError, primitives::proto::Protobuf};
+use tendermint::{abci::types::ExecTxResult, crypto::default::Sha256, merkle, Hash};
+
+/// `last_results_hash` of the header following a block whose transactions
+/// returned `results`.
+pub fn results_hash(results: &[ExecTxResult]) -> Hash {
+    let leaves: Vec<Vec<u8>> = results.iter().map(deterministic_bytes).collect();
+    Hash::Sha256(merkle::simple_hash_from_byte_vectors::<Sha256>(&leaves))
+}
+
+/// Verifies that `results` are the ones `last_results_hash` commits to.
+pub fn verify_results(
+    results: &[ExecTxResult],
+    last_results_hash: &Hash,
+) -> Result<(), ClientError> {
+    let hash = results_hash(results);
+    if hash != *last_results_hash {
+        return Err(ClientError::Other {
+            description: format!(
+                "results hash {hash} does not match the last results hash {last_results_hash}"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Protobuf encoding of the fields of `result` the hash commits to.
+fn deterministic_bytes(result: &ExecTxResult) -> Vec<u8> {
+    ExecTxResult {
+        code: result.code,
+        data: result.data.clone(),
+        gas_wanted: result.gas_wanted,
+        gas_used: result.gas_used,
+        ..Default::default()
+    }
+    .encode_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint::abci::{Event, EventAttributeIndexExt};
+
+    fn results() -> Vec<ExecTxResult> {
+        vec![
+            ExecTxResult {
+                data: b"one".to_vec().into(),
+                gas_wanted: 100,
+                gas_used: 80,
+                ..Default::default()
+            },
+            ExecTxResult {
+                code: 14.into(),
+                log: "out of gas".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn hashes_deterministic_fields() {
+        // protobuf of `Code: 14`, the only field set
+        assert_eq!(deterministic_bytes(&results()[1]), vec![0x08, 14]);
+
+        let hash = results_hash(&results());
+        verify_results(&results(), &hash).unwrap();
+
+        let mut changed = results();
+        changed[1].events = vec![Event::new("transfer", [("amount", "1").no_index()])];
+        changed[1].log = "other".into();
+        verify_results(&changed, &hash).unwrap();
+
+        changed[1].code = 0.into();
+        assert!(verify_results(&changed, &hash).is_err());
+        assert!(verify_results(&results()[..1], &hash).is_err());
+    }
+}
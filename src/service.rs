@@ -0,0 +1,477 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Path as UrlPath, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::Engine;
+use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState, Header};
+use ibc_core::{
+    client::{
+        context::{
+            client_state::ClientStateCommon, ClientValidationContext, ExtClientValidationContext,
+        },
+        types::{error::ClientError, Height},
+    },
+    commitment_types::commitment::{CommitmentPrefix, CommitmentProofBytes},
+    host::types::path::{ClientConsensusStatePath, Path},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::RwLock};
+
+use crate::{
+    batch::BatchVerifier, metrics::Metrics, provider::LightClientProvider,
+    utils::ConsensusStateFile,
+};
+
+/// Error answered as `{"error": "..."}` with its status code.
+pub struct ServiceError(StatusCode, String);
+
+impl ServiceError {
+    fn bad_request(e: impl ToString) -> Self {
+        Self(StatusCode::BAD_REQUEST, e.to_string())
+    }
+
+    fn unprocessable(e: impl ToString) -> Self {
+        Self(StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+    }
+
+    fn not_found(e: impl ToString) -> Self {
+        Self(StatusCode::NOT_FOUND, e.to_string())
+    }
+
+    /// The stored client is unreadable, not a fault of the request.
+    fn internal(e: impl ToString) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ServiceError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ServiceResult = Result<Json<Value>, ServiceError>;
+
+/// Light client shared by every request. Headers take the write lock, queries
+/// and proofs share the read lock.
+pub struct LightClientService {
+    pub(crate) client: RwLock<BatchVerifier>,
+    metrics: Metrics,
+}
+
+impl LightClientService {
+    /// Service with a client trusting `cs` at `client_state.latest_height`.
+    pub fn new(client_state: ClientStateType, cs: ConsensusState) -> Result<Self, Box<dyn Error>> {
+        let metrics = Metrics::default();
+        let verifier = BatchVerifier::new(client_state, cs, None)?.with_metrics(metrics.clone());
+        Ok(Self {
+            client: RwLock::new(verifier),
+            metrics,
+        })
+    }
+
+    /// Polls the chain head from `provider` every `interval`, so the lag of
+    /// the client behind it is reported in the metrics.
+    pub async fn watch_chain_head(&self, provider: LightClientProvider, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let result = provider.latest_height().await;
+            self.metrics.observe_rpc("latest_height", &result);
+            if let Ok(height) = result {
+                self.metrics.observe_chain_head(height);
+            }
+        }
+    }
+}
+
+/// Body of a membership or non-membership proof request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofRequest {
+    /// Height of the consensus state whose root the proof is against.
+    pub height: Height,
+    pub prefix: String,
+    /// IBC path, e.g. commitments/ports/transfer/channels/channel-0/sequences/1.
+    pub path: String,
+    /// Base64 `MerkleProof` proto bytes.
+    pub proof: String,
+    /// Base64 value, only for membership proofs.
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+fn decode_base64(field: &str, s: &str) -> Result<Vec<u8>, ServiceError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| ServiceError::bad_request(format!("invalid {field}: {e}")))
+}
+
+async fn status(State(service): State<Arc<LightClientService>>) -> ServiceResult {
+    let verifier = service.client.read().await;
+    let client = verifier
+        .client()
+        .client_state()
+        .map_err(ServiceError::internal)?;
+    let status = verifier.client().status().map_err(ServiceError::internal)?;
+
+    Ok(Json(json!({
+        "chain_id": client.inner().chain_id.to_string(),
+        "latest_height": client.latest_height(),
+        "status": status.to_string(),
+        "trust_threshold": client.inner().trust_level.to_string(),
+        "trusting_period_secs": client.inner().trusting_period.as_secs(),
+    })))
+}
+
+async fn metrics(State(service): State<Arc<LightClientService>>) -> String {
+    service
+        .metrics
+        .observe_client(&*service.client.read().await);
+    service.metrics.encode()
+}
+
+async fn consensus_state_heights(State(service): State<Arc<LightClientService>>) -> ServiceResult {
+    let verifier = service.client.read().await;
+    let mut heights = verifier
+        .ctx()
+        .consensus_state_heights(verifier.client_id())
+        .map_err(ServiceError::internal)?;
+    heights.sort();
+    Ok(Json(json!({ "heights": heights })))
+}
+
+async fn consensus_state(
+    State(service): State<Arc<LightClientService>>,
+    UrlPath(height): UrlPath<String>,
+) -> ServiceResult {
+    let height: Height = height.parse().map_err(ServiceError::bad_request)?;
+    let verifier = service.client.read().await;
+    let cs = verifier
+        .ctx()
+        .consensus_state(&ClientConsensusStatePath::new(
+            verifier.client_id().clone(),
+            height.revision_number(),
+            height.revision_height(),
+        ))
+        .map_err(ServiceError::not_found)?;
+    let file = ConsensusStateFile::from(cs.inner().clone());
+    Ok(Json(json!({ "height": height, "consensus_state": file })))
+}
+
+async fn submit_header(
+    State(service): State<Arc<LightClientService>>,
+    Json(header): Json<Header>,
+) -> ServiceResult {
+    let height = header.height();
+    let file = ConsensusStateFile::from(ConsensusState::from(header.clone()));
+    service
+        .client
+        .write()
+        .await
+        .apply(&header)
+        .map_err(ServiceError::unprocessable)?;
+    Ok(Json(json!({ "height": height, "consensus_state": file })))
+}
+
+async fn verify_proof(
+    service: &LightClientService,
+    req: ProofRequest,
+    membership: bool,
+) -> ServiceResult {
+    let prefix =
+        CommitmentPrefix::try_from(req.prefix.into_bytes()).map_err(ServiceError::bad_request)?;
+    let path: Path = req.path.parse().map_err(ServiceError::bad_request)?;
+    let proof = CommitmentProofBytes::try_from(decode_base64("proof", &req.proof)?)
+        .map_err(ServiceError::bad_request)?;
+
+    let verifier = service.client.read().await;
+    let client = verifier.client();
+    let result = if membership {
+        let value = decode_base64("value", req.value.as_deref().unwrap_or_default())?;
+        client.verify_membership(req.height, &prefix, &proof, path, value)
+    } else {
+        client.verify_non_membership(req.height, &prefix, &proof, path)
+    };
+    result.map_err(|e| match e {
+        ClientError::ClientNotActive { status } => {
+            ServiceError::unprocessable(format!("client is {status}, proofs are not verified"))
+        }
+        ClientError::ConsensusStateNotFound { .. } => ServiceError::not_found(e),
+        ClientError::ClientStateNotFound { .. } => ServiceError::internal(e),
+        e => ServiceError::unprocessable(e),
+    })?;
+    Ok(Json(json!({ "valid": true })))
+}
+
+async fn verify_membership(
+    State(service): State<Arc<LightClientService>>,
+    Json(req): Json<ProofRequest>,
+) -> ServiceResult {
+    verify_proof(&service, req, true).await
+}
+
+async fn verify_non_membership(
+    State(service): State<Arc<LightClientService>>,
+    Json(req): Json<ProofRequest>,
+) -> ServiceResult {
+    verify_proof(&service, req, false).await
+}
+
+/// Router of the light client service:
+///
+/// - `GET /status`: chain id, latest height and status of the client
+/// - `GET /consensus_states`: heights of the installed consensus states
+/// - `GET /consensus_states/{height}`: consensus state at a height, e.g. `0-5`
+/// - `POST /headers`: verifies a header and installs its consensus state
+/// - `POST /verify_membership`, `POST /verify_non_membership`: verifies a
+///   [`ProofRequest`] against an installed consensus state
+/// - `GET /metrics`: Prometheus metrics, see [`Metrics`]
+pub fn router(service: Arc<LightClientService>) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/consensus_states", get(consensus_state_heights))
+        .route("/consensus_states/:height", get(consensus_state))
+        .route("/headers", post(submit_header))
+        .route("/verify_membership", post(verify_membership))
+        .route("/verify_non_membership", post(verify_non_membership))
+        .route("/metrics", get(metrics))
+        .with_state(service)
+}
+
+/// Serves `service` on `listener` until the task is dropped.
+pub async fn serve(listener: TcpListener, service: Arc<LightClientService>) -> std::io::Result<()> {
+    axum::serve(listener, router(service)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use ibc_core::{
+        channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+        host::types::identifiers::ChainId,
+        primitives::Timestamp,
+    };
+    use reqwest::Client;
+    use tendermint::Time;
+
+    use crate::{
+        test_utils::{client_state, osmosis_proof},
+        testgen::{ChainSpec, TestChain},
+        utils::bytes_to_base64,
+    };
+
+    // A generated chain whose block 6 carries the app hash of the Osmosis proof,
+    // served with a client trusting height 2.
+    async fn spawn_service() -> (TestChain, String) {
+        let chain = ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            app_hashes: BTreeMap::from([(6, hex::encode_upper(osmosis_proof().root))]),
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new("testgen-0").unwrap();
+        client_state.latest_height = chain.ibc_height(2).unwrap();
+
+        let service =
+            LightClientService::new(client_state, chain.consensus_state(2).unwrap()).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, Arc::new(service)));
+        (chain, url)
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> (StatusCode, Value) {
+        let response = request.send().await.unwrap();
+        let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+        (status, response.json().await.unwrap())
+    }
+
+    fn proof_request(value: Vec<u8>) -> ProofRequest {
+        let osmosis = osmosis_proof();
+        ProofRequest {
+            height: Height::new(0, 6).unwrap(),
+            prefix: "ibc".to_string(),
+            path: osmosis.path().to_string(),
+            proof: bytes_to_base64(&osmosis.proof),
+            value: Some(bytes_to_base64(&value)),
+        }
+    }
+
+    #[tokio::test]
+    async fn submits_headers_and_queries_state() {
+        let (chain, url) = spawn_service().await;
+        let client = Client::new();
+
+        let (status, body) = send(client.get(format!("{url}/status"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["chain_id"], "testgen-0");
+        assert_eq!(body["status"], "Active");
+
+        // headers are submitted concurrently, each one trusts height 2
+        let submissions = [6, 4, 8].map(|height| {
+            let request = client
+                .post(format!("{url}/headers"))
+                .json(&chain.header(height, 2).unwrap());
+            tokio::spawn(send(request))
+        });
+        for submission in submissions {
+            assert_eq!(submission.await.unwrap().0, StatusCode::OK);
+        }
+
+        let (_, body) = send(client.get(format!("{url}/consensus_states"))).await;
+        let heights: Vec<Height> = serde_json::from_value(body["heights"].clone()).unwrap();
+        assert_eq!(heights, [2, 4, 6, 8].map(|h| Height::new(0, h).unwrap()));
+
+        let (status, body) = send(client.get(format!("{url}/consensus_states/0-6"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["consensus_state"]["root"],
+            hex::encode_upper(osmosis_proof().root)
+        );
+        let (status, _) = send(client.get(format!("{url}/consensus_states/0-5"))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let mut corrupted = chain.header(9, 8).unwrap();
+        corrupted.signed_header.commit.signatures.clear();
+        let (status, body) = send(client.post(format!("{url}/headers")).json(&corrupted)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["error"].is_string());
+
+        let metrics = client
+            .get(format!("{url}/metrics"))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(
+            metrics.contains("lightclient_trusted_height 8"),
+            "{metrics}"
+        );
+        assert!(metrics.contains("lightclient_verification_duration_seconds_count 4"));
+        assert!(metrics.contains("lightclient_verification_failures_total{kind="));
+    }
+
+    #[tokio::test]
+    async fn verifies_proofs() {
+        let (chain, url) = spawn_service().await;
+        let client = Client::new();
+        send(
+            client
+                .post(format!("{url}/headers"))
+                .json(&chain.header(6, 2).unwrap()),
+        )
+        .await;
+
+        let commitment = compute_packet_commitment(
+            &osmosis_proof().data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &Timestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec();
+        let (status, body) = send(
+            client
+                .post(format!("{url}/verify_membership"))
+                .json(&proof_request(commitment)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["valid"], true);
+
+        let (status, _) = send(
+            client
+                .post(format!("{url}/verify_membership"))
+                .json(&proof_request(b"forged".to_vec())),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // the path is set, so it has no non-membership proof
+        let (status, _) = send(
+            client
+                .post(format!("{url}/verify_non_membership"))
+                .json(&proof_request(vec![])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut request = proof_request(vec![]);
+        request.proof = "not base64!".to_string();
+        let (status, body) = send(
+            client
+                .post(format!("{url}/verify_membership"))
+                .json(&request),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("invalid proof"));
+
+        // no consensus state is installed at height 5
+        let mut request = proof_request(vec![]);
+        request.height = chain.ibc_height(5).unwrap();
+        let (status, _) = send(
+            client
+                .post(format!("{url}/verify_non_membership"))
+                .json(&request),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn freezes_on_conflicting_header() {
+        let (chain, url) = spawn_service().await;
+        let client = Client::new();
+        let commitment = compute_packet_commitment(
+            &osmosis_proof().data,
+            &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+            &Timestamp::from_nanoseconds(0).unwrap(),
+        )
+        .into_vec();
+        send(
+            client
+                .post(format!("{url}/headers"))
+                .json(&chain.header(6, 2).unwrap()),
+        )
+        .await;
+
+        // the same validators sign another block 6
+        let fork = ChainSpec {
+            app_hashes: BTreeMap::new(),
+            ..chain.spec().clone()
+        }
+        .generate()
+        .unwrap();
+        let (status, body) = send(
+            client
+                .post(format!("{url}/headers"))
+                .json(&fork.header(6, 2).unwrap()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["error"].as_str().unwrap().contains("frozen"), "{body}");
+
+        let (_, body) = send(client.get(format!("{url}/status"))).await;
+        assert_eq!(body["status"], "Frozen");
+        let (status, body) = send(
+            client
+                .post(format!("{url}/verify_membership"))
+                .json(&proof_request(commitment)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "client is Frozen, proofs are not verified");
+    }
+}
@@ -1,7 +1,5 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    ops::Bound,
-};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::ops::Bound;
 
 use ibc_core::{client::types::Height, primitives::Timestamp};
 
@@ -12,13 +10,49 @@ pub enum Direction {
     Previous,
 }
 
+/// Store of a single client that [`crate::context::Ctx`] reads and writes,
+/// either the in-memory [`Storage`] or a store of the host the client runs in.
+pub trait ClientStore<C: ClientType> {
+    fn client_state(&self) -> Option<C::ClientState>;
+
+    fn set_client_state(&mut self, client_state: C::ClientState);
+
+    fn consensus_state(&self, height: &Height) -> Option<C::ConsensusState>;
+
+    fn set_consensus_state(&mut self, height: Height, consensus_state: C::ConsensusState);
+
+    fn remove_consensus_state(&mut self, height: &Height);
+
+    /// Heights of every stored consensus state, in ascending order.
+    fn heights(&self) -> Vec<Height>;
+
+    /// Consensus state at the closest height after or before `current`.
+    fn adjacent_consensus_state(
+        &self,
+        current: &Height,
+        direction: Direction,
+    ) -> Option<C::ConsensusState>;
+
+    /// Host time and height the consensus state at `height` was stored at.
+    fn update_meta(&self, height: &Height) -> Option<(Timestamp, Height)>;
+
+    fn set_update_meta(&mut self, height: Height, meta: (Timestamp, Height));
+
+    fn remove_update_meta(&mut self, height: &Height);
+
+    /// Height of the host the store lives on, if it knows it.
+    fn current_height(&self) -> Option<Height> {
+        None
+    }
+}
+
 #[derive(Clone)]
 pub struct Storage<C: ClientType> {
+    /// Host height for contexts that do not set one themselves.
     pub current_height: Option<Height>,
     pub client_state: Option<C::ClientState>,
-    pub consensus_state: HashMap<String, C::ConsensusState>,
     pub consensus_state_height_map: BTreeMap<Height, C::ConsensusState>,
-    pub update_meta: HashMap<Height, (Timestamp, Height)>,
+    pub update_meta: BTreeMap<Height, (Timestamp, Height)>,
 }
 
 impl<C: ClientType> Default for Storage<C> {
@@ -26,19 +60,39 @@ impl<C: ClientType> Default for Storage<C> {
         Self {
             current_height: None,
             client_state: None,
-            consensus_state: HashMap::new(),
             consensus_state_height_map: BTreeMap::new(),
-            update_meta: HashMap::new(),
+            update_meta: BTreeMap::new(),
         }
     }
 }
 
-impl<C: ClientType> Storage<C> {
-    pub fn get_heights(&self) -> Vec<Height> {
+impl<C: ClientType> ClientStore<C> for Storage<C> {
+    fn client_state(&self) -> Option<C::ClientState> {
+        self.client_state.clone()
+    }
+
+    fn set_client_state(&mut self, client_state: C::ClientState) {
+        self.client_state = Some(client_state);
+    }
+
+    fn consensus_state(&self, height: &Height) -> Option<C::ConsensusState> {
+        self.consensus_state_height_map.get(height).cloned()
+    }
+
+    fn set_consensus_state(&mut self, height: Height, consensus_state: C::ConsensusState) {
+        self.consensus_state_height_map
+            .insert(height, consensus_state);
+    }
+
+    fn remove_consensus_state(&mut self, height: &Height) {
+        self.consensus_state_height_map.remove(height);
+    }
+
+    fn heights(&self) -> Vec<Height> {
         self.consensus_state_height_map.keys().cloned().collect()
     }
 
-    pub fn get_adjacent_height(
+    fn adjacent_consensus_state(
         &self,
         current: &Height,
         direction: Direction,
@@ -47,15 +101,31 @@ impl<C: ClientType> Storage<C> {
             Direction::Next => {
                 let mut it = self
                     .consensus_state_height_map
-                    .range((Bound::Included(current), Bound::Unbounded));
-                it.next().map(|(_, s)| s.to_owned())
+                    .range((Bound::Excluded(current), Bound::Unbounded));
+                it.next().map(|(_, s)| s.clone())
             }
             Direction::Previous => {
                 let mut it = self
                     .consensus_state_height_map
-                    .range((Bound::Unbounded, Bound::Included(current)));
-                it.next_back().map(|(_, s)| s.to_owned())
+                    .range((Bound::Unbounded, Bound::Excluded(current)));
+                it.next_back().map(|(_, s)| s.clone())
             }
         }
     }
+
+    fn update_meta(&self, height: &Height) -> Option<(Timestamp, Height)> {
+        self.update_meta.get(height).copied()
+    }
+
+    fn set_update_meta(&mut self, height: Height, meta: (Timestamp, Height)) {
+        self.update_meta.insert(height, meta);
+    }
+
+    fn remove_update_meta(&mut self, height: &Height) {
+        self.update_meta.remove(height);
+    }
+
+    fn current_height(&self) -> Option<Height> {
+        self.current_height
+    }
 }
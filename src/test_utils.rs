@@ -0,0 +1,136 @@
+use std::{
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ibc_client_tendermint::types::{ClientState as ClientStateType, ConsensusState, Header};
+use ibc_core::{
+    client::types::Height,
+    host::types::{
+        identifiers::{ChainId, ChannelId, PortId, Sequence},
+        path::{CommitmentPath, Path},
+    },
+};
+use ibc_proto::ibc::core::commitment::v1::MerkleProof;
+use prost::Message;
+use serde::Deserialize;
+use tendermint::merkle::proof::{ProofOp, ProofOps};
+use tokio::net::TcpListener;
+
+use crate::{
+    client_options::ClientOptions,
+    mock_rpc::{serve, MockChain},
+    testgen::TestChain,
+    utils::{base64_to_bytes, ConsensusStateFile},
+};
+
+pub fn get_header() -> Header {
+    serde_json::from_str::<Header>(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/data/header.json"
+    )))
+    .unwrap()
+}
+
+/// Trusted consensus state at height 0-6 of `chain2`, the header fixture trusts it.
+pub fn dummy_consensus_state() -> ConsensusState {
+    serde_json::from_str::<ConsensusStateFile>(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/data/consensus_state.json"
+    )))
+    .unwrap()
+    .into()
+}
+
+/// Client state for `chain2` with the default client parameters, whose five
+/// year trusting period keeps the 2023 fixtures valid.
+pub fn client_state() -> ClientStateType {
+    ClientOptions {
+        chain_id: ChainId::new("chain2").unwrap(),
+        ..ClientOptions::default()
+    }
+    .client_state(Height::new(0, 6).unwrap())
+    .unwrap()
+}
+
+/// [`client_state`] for the chain of `chain`.
+pub fn chain_client_state(chain: &TestChain) -> ClientStateType {
+    let mut client_state = client_state();
+    client_state.chain_id = ChainId::new(&chain.spec().chain_id).unwrap();
+    client_state
+}
+
+/// Proof of the Osmosis packet commitment in `src/data/proof.json`.
+pub struct OsmosisProof {
+    pub proof: Vec<u8>,
+    /// App hash the proof is against.
+    pub root: Vec<u8>,
+    /// Packet data, the committed value is computed from it.
+    pub data: Vec<u8>,
+}
+
+pub fn osmosis_proof() -> OsmosisProof {
+    #[derive(Deserialize)]
+    struct ProofData {
+        proof_commitment: String,
+        data: String,
+        root: String,
+    }
+
+    let proof_data = serde_json::from_str::<ProofData>(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/data/proof.json"
+    )))
+    .unwrap();
+    OsmosisProof {
+        proof: base64_to_bytes(&proof_data.proof_commitment),
+        root: base64_to_bytes(&proof_data.root),
+        data: base64_to_bytes(&proof_data.data),
+    }
+}
+
+impl OsmosisProof {
+    /// Path of the packet commitment the proof is for.
+    pub fn path(&self) -> Path {
+        Path::Commitment(CommitmentPath::new(
+            &PortId::transfer(),
+            &ChannelId::new(0),
+            Sequence::from(3514632),
+        ))
+    }
+
+    /// The proof split into the proof ops an `abci_query` returns, one per store.
+    pub fn proof_ops(&self) -> ProofOps {
+        let merkle_proof = MerkleProof::decode(self.proof.as_slice()).unwrap();
+        let ops = merkle_proof
+            .proofs
+            .iter()
+            .zip(["ics23:iavl", "ics23:simple"])
+            .map(|(proof, field_type)| ProofOp {
+                field_type: field_type.to_string(),
+                key: vec![],
+                data: proof.encode_to_vec(),
+            })
+            .collect();
+        ProofOps { ops }
+    }
+}
+
+/// Serves `chain` on a free local port and returns its url.
+pub async fn spawn_mock_rpc(chain: MockChain) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(serve(listener, chain));
+    url
+}
+
+/// Path in the temp dir that no other test or test run writes to.
+pub fn temp_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "tendermint-lightclient-{}-{}-{name}",
+        process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
@@ -0,0 +1,778 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path, str::FromStr, time::Duration};
+
+use clap::ValueEnum;
+use ibc_client_tendermint::types::{ConsensusState, Header};
+use ibc_core::{client::types::Height, host::types::identifiers::ChainId};
+use serde::{Deserialize, Serialize};
+use tendermint::{
+    abci::types::ExecTxResult, block::CommitSig, tx, validator, AppHash, Hash, Signature, Time,
+};
+use tendermint_testgen::{
+    light_block::TmLightBlock, validator::generate_validators, Commit, Generator,
+    Header as TestgenHeader, LightBlock, Validator,
+};
+
+use crate::{
+    inclusion::{data_hash, tx_proof},
+    results::results_hash,
+    utils::write_consensus_state,
+};
+
+/// Key type of a generated validator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyType {
+    #[default]
+    Ed25519,
+    /// Needs the `secp256k1` feature.
+    Secp256k1,
+}
+
+/// Validator of a generated chain. Its key is derived from `id`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidatorSpec {
+    pub id: String,
+    pub power: u64,
+    #[serde(default)]
+    pub key_type: KeyType,
+}
+
+/// Validator set that takes effect from `height` onwards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidatorChange {
+    pub height: u64,
+    pub validators: Vec<ValidatorSpec>,
+}
+
+/// Most blocks a [`ChainSpec`] may generate, they are all held in memory.
+pub const MAX_LENGTH: u64 = 100_000;
+
+/// Description of a generated chain. The same spec always generates the same chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainSpec {
+    pub chain_id: String,
+    pub start_height: u64,
+    pub length: u64,
+    pub start_time: Time,
+    pub block_interval_secs: u64,
+    pub validators: Vec<ValidatorSpec>,
+    #[serde(default)]
+    pub changes: Vec<ValidatorChange>,
+    /// Hex app hash of blocks by height, other blocks have an empty app hash.
+    #[serde(default)]
+    pub app_hashes: BTreeMap<u64, String>,
+    /// Hex transactions of blocks by height, other blocks have none.
+    #[serde(default)]
+    pub txs: BTreeMap<u64, Vec<String>>,
+    /// Results of the transactions of blocks by height, committed to by the
+    /// `last_results_hash` of the next block.
+    #[serde(default)]
+    pub tx_results: BTreeMap<u64, Vec<ExecTxResult>>,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self {
+            chain_id: "testgen-0".to_string(),
+            start_height: 1,
+            length: 10,
+            start_time: Time::from_str("2024-01-01T00:00:00Z").expect("Never fails"),
+            block_interval_secs: 5,
+            validators: ["a", "b", "c", "d"]
+                .iter()
+                .map(|id| ValidatorSpec {
+                    id: id.to_string(),
+                    power: 10,
+                    key_type: KeyType::Ed25519,
+                })
+                .collect(),
+            changes: vec![],
+            app_hashes: BTreeMap::new(),
+            txs: BTreeMap::new(),
+            tx_results: BTreeMap::new(),
+        }
+    }
+}
+
+/// Deliberately broken variants of a valid header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Corruption {
+    /// The first commit signature is altered.
+    BadSignature,
+    /// The validator set does not hash to the header's `validators_hash`.
+    WrongValidatorsHash,
+    /// The header is signed with a time in the far future.
+    FutureTime,
+}
+
+impl Corruption {
+    pub const ALL: [Corruption; 3] = [
+        Corruption::BadSignature,
+        Corruption::WrongValidatorsHash,
+        Corruption::FutureTime,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BadSignature => "bad_signature",
+            Self::WrongValidatorsHash => "wrong_validators_hash",
+            Self::FutureTime => "future_time",
+        }
+    }
+}
+
+impl ChainSpec {
+    fn validator_specs_at(&self, height: u64) -> &[ValidatorSpec] {
+        self.changes
+            .iter()
+            .filter(|change| change.height <= height)
+            .max_by_key(|change| change.height)
+            .map(|change| &change.validators)
+            .unwrap_or(&self.validators)
+    }
+
+    fn validators_at(&self, height: u64) -> Vec<Validator> {
+        self.validator_specs_at(height)
+            .iter()
+            .map(|v| Validator::new(&v.id).voting_power(v.power))
+            .collect()
+    }
+
+    fn has_secp256k1_at(&self, height: u64) -> bool {
+        [height, height + 1].iter().any(|&height| {
+            self.validator_specs_at(height)
+                .iter()
+                .any(|v| v.key_type == KeyType::Secp256k1)
+        })
+    }
+
+    fn time_at(&self, height: u64) -> Result<Time, Box<dyn Error>> {
+        let offset = (height - self.start_height)
+            .checked_mul(self.block_interval_secs)
+            .ok_or_else(|| format!("time of block {height} overflows"))?;
+        Ok((self.start_time + Duration::from_secs(offset))?)
+    }
+
+    fn light_block(
+        &self,
+        height: u64,
+        time: Time,
+        last_block_id_hash: Option<Hash>,
+    ) -> Result<TmLightBlock, Box<dyn Error>> {
+        let mut header = TestgenHeader::new(&self.validators_at(height))
+            .next_validators(&self.validators_at(height + 1))
+            .chain_id(&self.chain_id)
+            .height(height)
+            .time(time);
+        if let Some(hash) = last_block_id_hash {
+            header = header.last_block_id_hash(hash);
+        }
+        if let Some(app_hash) = self.app_hashes.get(&height) {
+            header = header.app_hash(AppHash::try_from(hex::decode(app_hash)?)?);
+        }
+        let commit = Commit::new(header.clone(), 1);
+        let mut block = LightBlock::new(header, commit).generate()?;
+
+        // testgen only generates ed25519 validators and no `data_hash` or
+        // `last_results_hash`, other blocks are signed here.
+        let txs = self.txs_at(height)?;
+        let last_results = height
+            .checked_sub(1)
+            .and_then(|height| self.tx_results.get(&height));
+        if txs.is_empty() && last_results.is_none() && !self.has_secp256k1_at(height) {
+            return Ok(block);
+        }
+        if !txs.is_empty() {
+            block.signed_header.header.data_hash = Some(data_hash(&txs));
+        }
+        if let Some(results) = last_results {
+            block.signed_header.header.last_results_hash = Some(results_hash(results));
+        }
+        signing::resign(
+            block,
+            self.validator_specs_at(height),
+            self.validator_specs_at(height + 1),
+        )
+    }
+
+    /// Checks the heights of the spec, every other height is derived from them.
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.start_height == 0 {
+            return Err("`start_height` must be at least 1".into());
+        }
+        if self.length == 0 {
+            return Err("`length` must be at least 1".into());
+        }
+        if self.length > MAX_LENGTH {
+            return Err(format!("`length` must be at most {MAX_LENGTH}").into());
+        }
+        if self.start_height.checked_add(self.length).is_none() {
+            return Err("the chain ends after the largest height".into());
+        }
+        Ok(())
+    }
+
+    /// Transactions of the block at `height`.
+    pub fn txs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        Ok(self
+            .txs
+            .get(&height)
+            .map(|txs| txs.iter().map(hex::decode).collect::<Result<_, _>>())
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Generates the signed headers of the chain.
+    pub fn generate(&self) -> Result<TestChain, Box<dyn Error>> {
+        self.validate()?;
+
+        let mut blocks = Vec::with_capacity(self.length as usize);
+        let mut last_block_id_hash = None;
+        for height in self.start_height..self.start_height + self.length {
+            let block = self.light_block(height, self.time_at(height)?, last_block_id_hash)?;
+            last_block_id_hash = Some(block.signed_header.header.hash());
+            blocks.push(block);
+        }
+
+        Ok(TestChain {
+            spec: self.clone(),
+            blocks,
+        })
+    }
+}
+
+/// Chain of signed headers generated from a [`ChainSpec`].
+pub struct TestChain {
+    spec: ChainSpec,
+    blocks: Vec<TmLightBlock>,
+}
+
+impl TestChain {
+    pub fn heights(&self) -> impl Iterator<Item = u64> {
+        self.spec.start_height..self.spec.start_height + self.spec.length
+    }
+
+    pub fn spec(&self) -> &ChainSpec {
+        &self.spec
+    }
+
+    pub fn light_blocks(&self) -> &[TmLightBlock] {
+        &self.blocks
+    }
+
+    pub fn light_block(&self, height: u64) -> Result<&TmLightBlock, Box<dyn Error>> {
+        height
+            .checked_sub(self.spec.start_height)
+            .and_then(|i| self.blocks.get(i as usize))
+            .ok_or_else(|| format!("height {height} is not in the generated chain").into())
+    }
+
+    /// IBC height of a block of the chain.
+    pub fn ibc_height(&self, height: u64) -> Result<Height, Box<dyn Error>> {
+        let revision = ChainId::new(&self.spec.chain_id)?.revision_number();
+        Ok(Height::new(revision, height)?)
+    }
+
+    /// Inclusion proof of the transaction at `index` in the block at `height`.
+    pub fn tx_proof(&self, height: u64, index: usize) -> Result<tx::Proof, Box<dyn Error>> {
+        tx_proof(&self.spec.txs_at(height)?, index)
+            .ok_or_else(|| format!("block {height} has no transaction {index}").into())
+    }
+
+    pub fn consensus_state(&self, height: u64) -> Result<ConsensusState, Box<dyn Error>> {
+        Ok(ConsensusState::from(
+            self.light_block(height)?.signed_header.header.clone(),
+        ))
+    }
+
+    /// Header at `height` for a client that trusts `trusted_height`.
+    pub fn header(&self, height: u64, trusted_height: u64) -> Result<Header, Box<dyn Error>> {
+        let block = self.light_block(height)?;
+        self.ibc_header(block, trusted_height)
+    }
+
+    /// Like [`TestChain::header`], broken the way `corruption` describes.
+    pub fn corrupted_header(
+        &self,
+        height: u64,
+        trusted_height: u64,
+        corruption: Corruption,
+    ) -> Result<Header, Box<dyn Error>> {
+        match corruption {
+            Corruption::BadSignature => {
+                let mut header = self.header(height, trusted_height)?;
+                let sig = header
+                    .signed_header
+                    .commit
+                    .signatures
+                    .iter_mut()
+                    .find_map(|sig| match sig {
+                        CommitSig::BlockIdFlagCommit { signature, .. } => signature.as_mut(),
+                        _ => None,
+                    })
+                    .ok_or("header has no commit signature")?;
+                let mut bytes = sig.as_bytes().to_vec();
+                bytes[0] ^= 0xff;
+                *sig = Signature::new(bytes)?.ok_or("empty signature")?;
+                Ok(header)
+            }
+            Corruption::WrongValidatorsHash => {
+                let mut header = self.header(height, trusted_height)?;
+                let mut validators = self.spec.validators_at(height);
+                validators.push(Validator::new("intruder").voting_power(1));
+                header.validator_set =
+                    validator::Set::without_proposer(generate_validators(&validators)?);
+                Ok(header)
+            }
+            Corruption::FutureTime => {
+                let last_block_id_hash = self
+                    .light_block(height - 1)
+                    .ok()
+                    .map(|block| block.signed_header.header.hash());
+                let far_future = Time::from_str("2200-01-01T00:00:00Z")?;
+                let block = self
+                    .spec
+                    .light_block(height, far_future, last_block_id_hash)?;
+                self.ibc_header(&block, trusted_height)
+            }
+        }
+    }
+
+    fn ibc_header(
+        &self,
+        block: &TmLightBlock,
+        trusted_height: u64,
+    ) -> Result<Header, Box<dyn Error>> {
+        let proposer = block.signed_header.header.proposer_address;
+        Ok(Header {
+            signed_header: block.signed_header.clone(),
+            validator_set: validator::Set::with_proposer(
+                block.validators.validators().clone(),
+                proposer,
+            )?,
+            trusted_height: self.ibc_height(trusted_height)?,
+            trusted_next_validator_set: self.light_block(trusted_height)?.next_validators.clone(),
+        })
+    }
+
+    /// Writes fixtures to `dir`: the consensus state of the first block, every
+    /// following header trusting its predecessor, and the broken variants of
+    /// the last header if it has a predecessor.
+    pub fn write_fixtures(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let start = self.spec.start_height;
+        let last = start + self.spec.length - 1;
+
+        write_consensus_state(
+            dir.join(format!("consensus_state_{start}.json"))
+                .to_str()
+                .ok_or("invalid path")?,
+            self.consensus_state(start)?,
+        )?;
+        for height in self.heights().skip(1) {
+            fs::write(
+                dir.join(format!("header_{height}.json")),
+                serde_json::to_string_pretty(&self.header(height, height - 1)?)?,
+            )?;
+        }
+        // a single block has no trusted predecessor to corrupt a header against
+        let corruptions = if last > start {
+            Corruption::ALL.as_slice()
+        } else {
+            &[]
+        };
+        for &corruption in corruptions {
+            fs::write(
+                dir.join(format!("header_{last}_{}.json", corruption.name())),
+                serde_json::to_string_pretty(&self.corrupted_header(
+                    last,
+                    last - 1,
+                    corruption,
+                )?)?,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Keys and commit signatures of blocks testgen cannot sign: validator sets
+/// with secp256k1 keys and headers with a `data_hash`.
+mod signing {
+    use std::error::Error;
+
+    #[cfg(feature = "secp256k1")]
+    use k256::ecdsa::signature::Signer;
+    #[cfg(feature = "secp256k1")]
+    use tendermint::private_key::Secp256k1;
+    use tendermint::{
+        block::{self, parts::Header as PartSetHeader, signed_header::SignedHeader, CommitSig},
+        validator,
+        vote::{Type, ValidatorIndex, Vote},
+        PublicKey, Signature,
+    };
+    use tendermint_testgen::{light_block::TmLightBlock, Validator};
+
+    use super::{KeyType, ValidatorSpec};
+
+    enum SigningKey {
+        Ed25519(ed25519_consensus::SigningKey),
+        #[cfg(feature = "secp256k1")]
+        Secp256k1(Secp256k1),
+    }
+
+    impl SigningKey {
+        /// Derives the key from the validator id, the same way testgen does for ed25519.
+        fn new(spec: &ValidatorSpec) -> Result<Self, Box<dyn Error>> {
+            let seed = Validator::new(&spec.id).get_private_key()?;
+            Ok(match spec.key_type {
+                KeyType::Ed25519 => Self::Ed25519(seed.try_into()?),
+                #[cfg(feature = "secp256k1")]
+                KeyType::Secp256k1 => Self::Secp256k1(Secp256k1::from_slice(seed.as_bytes())?),
+                #[cfg(not(feature = "secp256k1"))]
+                KeyType::Secp256k1 => {
+                    return Err("secp256k1 validators need the `secp256k1` feature".into())
+                }
+            })
+        }
+
+        fn public_key(&self) -> PublicKey {
+            match self {
+                Self::Ed25519(key) => PublicKey::from(key.verification_key()),
+                #[cfg(feature = "secp256k1")]
+                Self::Secp256k1(key) => PublicKey::from(*key.verifying_key()),
+            }
+        }
+
+        fn sign(&self, msg: &[u8]) -> Result<Signature, Box<dyn Error>> {
+            let bytes = match self {
+                Self::Ed25519(key) => key.sign(msg).to_bytes().to_vec(),
+                #[cfg(feature = "secp256k1")]
+                Self::Secp256k1(key) => {
+                    let signature: k256::ecdsa::Signature = key.sign(msg);
+                    signature.to_vec()
+                }
+            };
+            Ok(Signature::new(bytes)?.ok_or("empty signature")?)
+        }
+    }
+
+    fn validator_set(
+        specs: &[ValidatorSpec],
+    ) -> Result<(validator::Set, Vec<SigningKey>), Box<dyn Error>> {
+        let keys = specs
+            .iter()
+            .map(SigningKey::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let infos = specs
+            .iter()
+            .zip(&keys)
+            .map(|(spec, key)| {
+                Ok(validator::Info::new(
+                    key.public_key(),
+                    spec.power.try_into()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        Ok((validator::Set::without_proposer(infos), keys))
+    }
+
+    /// Replaces the validator sets of `block` with the ones of `specs` and
+    /// `next_specs`, and signs its commit with every validator.
+    pub(super) fn resign(
+        block: TmLightBlock,
+        specs: &[ValidatorSpec],
+        next_specs: &[ValidatorSpec],
+    ) -> Result<TmLightBlock, Box<dyn Error>> {
+        let (validators, keys) = validator_set(specs)?;
+        let (next_validators, _) = validator_set(next_specs)?;
+
+        let mut header = block.signed_header.header;
+        header.validators_hash = validators.hash();
+        header.next_validators_hash = next_validators.hash();
+        header.proposer_address = validators
+            .validators()
+            .first()
+            .ok_or_else(|| format!("block {} has no validators", header.height))?
+            .address;
+
+        let hash = header.hash();
+        let block_id = block::Id {
+            hash,
+            part_set_header: PartSetHeader::new(1, hash)?,
+        };
+        let round = block.signed_header.commit.round;
+
+        let mut signatures = Vec::with_capacity(validators.validators().len());
+        for (index, info) in validators.validators().iter().enumerate() {
+            let key = keys
+                .iter()
+                .find(|key| key.public_key() == info.pub_key)
+                .ok_or("validator has no key")?;
+            let vote = Vote {
+                vote_type: Type::Precommit,
+                height: header.height,
+                round,
+                block_id: Some(block_id),
+                timestamp: Some(header.time),
+                validator_address: info.address,
+                validator_index: ValidatorIndex::try_from(index)?,
+                signature: None,
+                extension: vec![],
+                extension_signature: None,
+            };
+            let sign_bytes = vote.into_signable_vec(header.chain_id.clone());
+            signatures.push(CommitSig::BlockIdFlagCommit {
+                validator_address: info.address,
+                timestamp: header.time,
+                signature: Some(key.sign(&sign_bytes)?),
+            });
+        }
+
+        let commit = block::Commit {
+            height: header.height,
+            round,
+            block_id,
+            signatures,
+        };
+        Ok(TmLightBlock {
+            signed_header: SignedHeader::new(header, commit)?,
+            validators,
+            next_validators,
+            provider: block.provider,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ibc_client_tendermint::client_state::ClientState;
+    use ibc_core::{
+        client::context::client_state::{ClientStateExecution, ClientStateValidation},
+        host::types::identifiers::ClientId,
+    };
+
+    use crate::{
+        api::TendermintClient,
+        context::Ctx,
+        test_utils::{client_state, temp_path},
+    };
+
+    // The client checks headers against the wall clock, so the chain must be recent.
+    fn recent_spec() -> ChainSpec {
+        ChainSpec {
+            start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+            changes: vec![ValidatorChange {
+                height: 6,
+                validators: ["a", "b", "c", "e"]
+                    .iter()
+                    .map(|id| ValidatorSpec {
+                        id: id.to_string(),
+                        power: 10,
+                        key_type: KeyType::Ed25519,
+                    })
+                    .collect(),
+            }],
+            ..ChainSpec::default()
+        }
+    }
+
+    fn mixed_key_spec() -> ChainSpec {
+        let validators = |ids: &[(&str, KeyType)]| {
+            ids.iter()
+                .map(|&(id, key_type)| ValidatorSpec {
+                    id: id.to_string(),
+                    power: 10,
+                    key_type,
+                })
+                .collect()
+        };
+        ChainSpec {
+            validators: validators(&[
+                ("a", KeyType::Ed25519),
+                ("b", KeyType::Secp256k1),
+                ("c", KeyType::Secp256k1),
+                ("d", KeyType::Ed25519),
+            ]),
+            changes: vec![ValidatorChange {
+                height: 6,
+                validators: validators(&[
+                    ("a", KeyType::Ed25519),
+                    ("b", KeyType::Secp256k1),
+                    ("c", KeyType::Secp256k1),
+                    ("e", KeyType::Secp256k1),
+                ]),
+            }],
+            ..recent_spec()
+        }
+    }
+
+    fn trusting_client(
+        chain: &TestChain,
+        trusted_height: u64,
+    ) -> (ClientState, Ctx<TendermintClient>, ClientId) {
+        let mut client_state = client_state();
+        client_state.chain_id = ChainId::new(&chain.spec.chain_id).unwrap();
+        client_state.latest_height = chain.ibc_height(trusted_height).unwrap();
+        let client = ClientState::from(client_state);
+
+        let mut ctx = Ctx::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        client
+            .initialise(
+                &mut ctx,
+                &client_id,
+                chain.consensus_state(trusted_height).unwrap().into(),
+            )
+            .unwrap();
+        (client, ctx, client_id)
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        let a = ChainSpec::default().generate().unwrap();
+        let b = ChainSpec::default().generate().unwrap();
+
+        for height in a.heights() {
+            assert_eq!(
+                a.light_block(height).unwrap(),
+                b.light_block(height).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_heights() {
+        for (start_height, length, expected) in [
+            (0, 10, "`start_height` must be at least 1"),
+            (1, 0, "`length` must be at least 1"),
+            (u64::MAX, 1, "the chain ends after the largest height"),
+            (1, MAX_LENGTH + 1, "`length` must be at most 100000"),
+        ] {
+            let spec = ChainSpec {
+                start_height,
+                length,
+                ..ChainSpec::default()
+            };
+            let err = spec.generate().err().unwrap();
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_block_time() {
+        let spec = ChainSpec {
+            block_interval_secs: u64::MAX,
+            ..ChainSpec::default()
+        };
+        assert!(spec.generate().is_err());
+        let err = spec.time_at(3).unwrap_err();
+        assert_eq!(err.to_string(), "time of block 3 overflows");
+    }
+
+    #[test]
+    fn writes_fixtures_of_single_block() {
+        let spec = ChainSpec {
+            length: 1,
+            ..recent_spec()
+        };
+        let dir = temp_path("single-block");
+        spec.generate().unwrap().write_fixtures(&dir).unwrap();
+
+        let files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, ["consensus_state_1.json"]);
+    }
+
+    #[test]
+    fn rejects_empty_validator_set() {
+        let spec = ChainSpec {
+            changes: vec![ValidatorChange {
+                height: 3,
+                validators: vec![],
+            }],
+            txs: [(3, vec![hex::encode("tx")])].into(),
+            ..recent_spec()
+        };
+        let err = spec.generate().err().unwrap();
+        assert_eq!(err.to_string(), "block 3 has no validators");
+    }
+
+    #[test]
+    fn generated_headers_verify() {
+        let chain = recent_spec().generate().unwrap();
+        let (client, ctx, client_id) = trusting_client(&chain, 1);
+
+        // adjacent
+        client
+            .verify_client_message(&ctx, &client_id, chain.header(2, 1).unwrap().into())
+            .expect("adjacent header");
+        // skipping over the validator set change, 3 of 4 trusted validators still sign
+        client
+            .verify_client_message(&ctx, &client_id, chain.header(8, 1).unwrap().into())
+            .expect("non adjacent header");
+    }
+
+    #[test]
+    fn corrupted_headers_fail() {
+        let chain = recent_spec().generate().unwrap();
+        let (client, ctx, client_id) = trusting_client(&chain, 1);
+
+        for corruption in Corruption::ALL {
+            let header = chain.corrupted_header(2, 1, corruption).unwrap();
+            let err = client
+                .verify_client_message(&ctx, &client_id, header.into())
+                .expect_err(corruption.name());
+
+            let expected = match corruption {
+                Corruption::BadSignature => "failed to verify signature",
+                Corruption::WrongValidatorsHash => "hash of the validators does not matches",
+                Corruption::FutureTime => "header from the future",
+            };
+            assert!(
+                err.to_string().contains(expected),
+                "{}: {err}",
+                corruption.name()
+            );
+        }
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn mixed_key_headers_verify() {
+        let chain = mixed_key_spec().generate().unwrap();
+        let (client, ctx, client_id) = trusting_client(&chain, 1);
+
+        for (height, trusted_height) in [(2, 1), (8, 1)] {
+            // headers must survive the fixture files
+            let header = chain.header(height, trusted_height).unwrap();
+            let header: Header =
+                serde_json::from_str(&serde_json::to_string(&header).unwrap()).unwrap();
+            client
+                .verify_client_message(&ctx, &client_id, header.into())
+                .unwrap_or_else(|err| panic!("header {height}: {err}"));
+        }
+
+        let header = chain
+            .corrupted_header(2, 1, Corruption::BadSignature)
+            .unwrap();
+        let err = client
+            .verify_client_message(&ctx, &client_id, header.into())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("failed to verify signature"),
+            "{err}"
+        );
+    }
+
+    #[cfg(not(feature = "secp256k1"))]
+    #[test]
+    fn secp256k1_needs_feature() {
+        let err = mixed_key_spec().generate().err().unwrap();
+        assert!(err.to_string().contains("`secp256k1` feature"), "{err}");
+    }
+}
@@ -1,28 +1,69 @@
 use std::io::Write;
-use std::{error::Error, fs::File};
+use std::{error::Error, fs, fs::File};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tendermint::{Hash, Time};
 
 use base64::Engine;
 use ibc_client_tendermint::types::ConsensusState;
+use ibc_core::{
+    commitment_types::commitment::{CommitmentProofBytes, CommitmentRoot},
+    host::types::path::Path,
+};
 pub fn base64_to_bytes(base64_str: &str) -> Vec<u8> {
     base64::engine::general_purpose::STANDARD
         .decode(base64_str)
         .unwrap()
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct CSReadable {
+/// Decodes base64 given on the command line, unlike [`base64_to_bytes`]
+/// without panicking on malformed input.
+pub fn parse_base64(s: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| format!("expected base64, got `{s}`: {e}"))
+}
+
+pub fn bytes_to_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Parses a transaction hash written as hex, in upper or lower case.
+pub fn parse_tx_hash(s: &str) -> Result<Hash, String> {
+    s.to_uppercase()
+        .parse()
+        .map_err(|_| format!("expected a hex SHA-256 hash, got `{s}`"))
+}
+
+/// Consensus state file format. Every command that reads or writes a consensus
+/// state file (`cs_path`, `new_cs_path`, `fetch-consensus-state` output) uses it:
+///
+/// ```json
+/// {
+///   "root": "7D3E0505A7AD774F4829E3F543E6693A1DBC78D2F884A721D3E0671AB1F005DD",
+///   "timestamp": "2023-03-10T12:18:59.96349Z",
+///   "next_validators_hash": "46DED613D8C7893433B18818CF0FF8D2E918F9A3CE824CAD76FDDAC1F1BAFAF5"
+/// }
+/// ```
+///
+/// `root` is the app hash as upper case hex. For backward compatibility it is
+/// also accepted as a JSON byte array, which is how the old `CSReadable`
+/// files stored it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConsensusStateFile {
+    #[serde(
+        serialize_with = "serialize_root",
+        deserialize_with = "deserialize_root"
+    )]
     root: Vec<u8>,
     timestamp: Time,
     next_validators_hash: Hash,
 }
 
-impl From<ConsensusState> for CSReadable {
+impl From<ConsensusState> for ConsensusStateFile {
     fn from(cs: ConsensusState) -> Self {
         let root = cs.root.clone();
-        CSReadable {
+        ConsensusStateFile {
             root: root.into_vec(),
             timestamp: cs.timestamp(),
             next_validators_hash: cs.next_validators_hash,
@@ -30,6 +71,70 @@ impl From<ConsensusState> for CSReadable {
     }
 }
 
+impl From<ConsensusStateFile> for ConsensusState {
+    fn from(file: ConsensusStateFile) -> Self {
+        ConsensusState::new(
+            CommitmentRoot::from_bytes(&file.root),
+            file.timestamp,
+            file.next_validators_hash,
+        )
+    }
+}
+
+fn serialize_root<S: Serializer>(root: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode_upper(root))
+}
+
+fn deserialize_root<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Root {
+        Hex(String),
+        Legacy(Vec<u8>),
+    }
+
+    match Root::deserialize(deserializer)? {
+        Root::Hex(s) => hex::decode(s).map_err(serde::de::Error::custom),
+        Root::Legacy(bytes) => Ok(bytes),
+    }
+}
+
+/// Reads a consensus state from a file in the [`ConsensusStateFile`] format,
+/// or in the JSON format of ibc-rs' `ConsensusState` that `verify` used to read.
+pub fn read_consensus_state(path: &str) -> Result<ConsensusState, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    match serde_json::from_str::<ConsensusStateFile>(&content) {
+        Ok(file) => Ok(file.into()),
+        Err(e) => serde_json::from_str::<ConsensusState>(&content).map_err(|_| e.into()),
+    }
+}
+
+/// Writes a consensus state to a file in the [`ConsensusStateFile`] format.
+pub fn write_consensus_state(path: &str, cs: ConsensusState) -> Result<(), Box<dyn Error>> {
+    let mut file = File::create(path)?;
+    file.write_all(serde_json::to_string_pretty(&ConsensusStateFile::from(cs))?.as_bytes())?;
+    Ok(())
+}
+
+/// Reads a commitment proof stored as base64 proto bytes, as `proof_commitment`
+/// appears in IBC packet messages.
+pub fn read_commitment_proof(path: &str) -> Result<CommitmentProofBytes, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let proof_bytes = base64::engine::general_purpose::STANDARD.decode(content.trim())?;
+    Ok(CommitmentProofBytes::try_from(proof_bytes)?)
+}
+
+/// Writes a commitment proof as base64 proto bytes, the format
+/// [`read_commitment_proof`] reads.
+pub fn write_commitment_proof(
+    path: &str,
+    proof: &CommitmentProofBytes,
+) -> Result<(), Box<dyn Error>> {
+    let bytes: &[u8] = proof.as_ref();
+    fs::write(path, bytes_to_base64(bytes))?;
+    Ok(())
+}
+
 pub async fn fetch_consensus_state(
     url_str: String,
     output_path: String,
@@ -38,11 +143,9 @@ pub async fn fetch_consensus_state(
 
     let provider = LightClientProvider::new(url_str.parse().unwrap());
 
-    let mut file = File::create(output_path)?;
     let cs = provider.consensus_state(6).await;
 
-    file.write_all(serde_json::to_string(&CSReadable::from(cs))?.as_bytes())?;
-    Ok(())
+    write_consensus_state(&output_path, cs)
 }
 
 pub async fn fetch_header(
@@ -58,3 +161,78 @@ pub async fn fetch_header(
     file.write_all(serde_json::to_string(&cs)?.as_bytes())?;
     Ok(())
 }
+
+/// Fetches the ICS-23 proof of an IBC path (e.g.
+/// `commitments/ports/transfer/channels/channel-0/sequences/1`) at `height`,
+/// writes the proof to `output_path` and returns the queried value.
+pub async fn fetch_proof(
+    url_str: String,
+    ibc_path: Path,
+    height: u64,
+    output_path: String,
+) -> Result<crate::provider::IbcQueryResponse, Box<dyn Error>> {
+    use crate::provider::LightClientProvider;
+
+    let provider = LightClientProvider::new(url_str.parse()?);
+    let response = provider.query_ibc_proof(&ibc_path, height).await?;
+    write_commitment_proof(&output_path, &response.proof)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{get_header, temp_path};
+
+    #[test]
+    fn consensus_state_file_round_trip() {
+        // `update` writes the consensus state of the header, `verify`/`update` read it back.
+        let cs = ConsensusState::from(get_header());
+        let path = temp_path("cs-round-trip.json");
+        let path = path.to_str().unwrap();
+
+        write_consensus_state(path, cs.clone()).expect("write consensus state");
+        let read = read_consensus_state(path).expect("read consensus state");
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(read, cs);
+        assert_eq!(
+            hex::encode_upper(read.root.as_bytes()),
+            "7D3E0505A7AD774F4829E3F543E6693A1DBC78D2F884A721D3E0671AB1F005DD"
+        );
+    }
+
+    #[test]
+    fn consensus_state_file_accepts_legacy_root() {
+        let cs = ConsensusState::from(get_header());
+        let legacy = format!(
+            r#"{{"root":{:?},"timestamp":"{}","next_validators_hash":"{}"}}"#,
+            cs.root.as_bytes(),
+            cs.timestamp.to_rfc3339(),
+            cs.next_validators_hash
+        );
+
+        let file: ConsensusStateFile = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(ConsensusState::from(file), cs);
+    }
+
+    #[test]
+    fn reads_ibc_rs_consensus_state() {
+        let cs = ConsensusState::from(get_header());
+        let path = temp_path("cs-ibc-rs.json");
+        let path = path.to_str().unwrap();
+
+        fs::write(path, serde_json::to_string(&cs).unwrap()).unwrap();
+        let read = read_consensus_state(path).expect("read consensus state");
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(read, cs);
+    }
+
+    #[test]
+    fn parses_base64_arguments() {
+        assert_eq!(parse_base64("AQI=").unwrap(), vec![1, 2]);
+        assert!(parse_base64("not base64!").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use std::{error::Error, fmt};
+
+use ibc_client_tendermint::types::{
+    ClientState as ClientStateType, ConsensusState, Header, Misbehaviour,
+};
+use ibc_core::{client::types::Height, host::types::identifiers::ClientId};
+use tendermint_rpc::Url;
+
+use crate::{
+    client_message::{verify_client_message, TmClientMessage},
+    provider::LightClientProvider,
+};
+
+/// Outcome of comparing the primary's header with a witness's header at the same height.
+pub enum WitnessReport {
+    /// The witness returned the same header.
+    Agrees,
+    /// The witness returned a different header that is valid too, so the chain
+    /// forked or the primary is attacking the client. The misbehaviour holds
+    /// the primary's header first and can be submitted to the client.
+    Conflict(Box<Misbehaviour>),
+    /// The witness could not be queried, or its header does not verify.
+    Faulty(String),
+}
+
+impl fmt::Display for WitnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Agrees => write!(f, "agrees"),
+            Self::Conflict(misbehaviour) => write!(
+                f,
+                "conflicting header {}",
+                misbehaviour.header2().signed_header.header.hash()
+            ),
+            Self::Faulty(reason) => write!(f, "faulty: {reason}"),
+        }
+    }
+}
+
+/// Primary header checked against the witnesses.
+pub struct CrossCheck {
+    /// Header of the primary, verified against the trusted consensus state.
+    pub header: Header,
+    pub witnesses: Vec<(Url, WitnessReport)>,
+}
+
+impl CrossCheck {
+    pub fn conflicts(&self) -> impl Iterator<Item = &Misbehaviour> {
+        self.witnesses
+            .iter()
+            .filter_map(|(_, report)| match report {
+                WitnessReport::Conflict(misbehaviour) => Some(misbehaviour.as_ref()),
+                _ => None,
+            })
+    }
+}
+
+/// Verifies the header at `height` from `primary` against `trusted_cs` at
+/// `trusted_height`, then fetches the header at the same height from every
+/// witness. A witness header that differs is verified as a [`Misbehaviour`]
+/// together with the primary header.
+pub async fn cross_check(
+    primary: Url,
+    witnesses: Vec<Url>,
+    client_state: ClientStateType,
+    client_id: &ClientId,
+    trusted_cs: &ConsensusState,
+    trusted_height: Height,
+    height: u32,
+) -> Result<CrossCheck, Box<dyn Error>> {
+    let trusted = std::slice::from_ref(trusted_cs);
+    let header = LightClientProvider::new(primary)
+        .header_trusting(height, trusted_height)
+        .await?;
+    verify_client_message(
+        client_state.clone(),
+        client_id,
+        &TmClientMessage::Header(Box::new(header.clone())),
+        trusted,
+        None,
+    )?;
+
+    let mut reports = Vec::with_capacity(witnesses.len());
+    for witness in witnesses {
+        let report = match LightClientProvider::new(witness.clone())
+            .header_trusting(height, trusted_height)
+            .await
+        {
+            Err(e) => WitnessReport::Faulty(e.to_string()),
+            Ok(witness_header)
+                if witness_header.signed_header.header.hash()
+                    == header.signed_header.header.hash() =>
+            {
+                WitnessReport::Agrees
+            }
+            Ok(witness_header) => {
+                let misbehaviour =
+                    Misbehaviour::new(client_id.clone(), header.clone(), witness_header);
+                match verify_client_message(
+                    client_state.clone(),
+                    client_id,
+                    &TmClientMessage::Misbehaviour(Box::new(misbehaviour.clone())),
+                    trusted,
+                    None,
+                ) {
+                    Ok(true) => WitnessReport::Conflict(Box::new(misbehaviour)),
+                    Ok(false) => WitnessReport::Faulty("header is not a misbehaviour".into()),
+                    Err(e) => WitnessReport::Faulty(e.to_string()),
+                }
+            }
+        };
+        reports.push((witness, report));
+    }
+
+    Ok(CrossCheck {
+        header,
+        witnesses: reports,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    use tendermint::Time;
+
+    use crate::{
+        mock_rpc::MockChain,
+        test_utils::{chain_client_state, spawn_mock_rpc},
+        testgen::{ChainSpec, TestChain},
+    };
+
+    fn chain(app_hashes: BTreeMap<u64, String>, start_time: Time) -> TestChain {
+        ChainSpec {
+            start_time,
+            app_hashes,
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn detects_fork_and_faulty_witness() {
+        let start_time = (Time::now() - Duration::from_secs(3600)).unwrap();
+        let honest = chain(BTreeMap::new(), start_time);
+        // Same validators sign another app hash at height 5.
+        let forked = chain(BTreeMap::from([(5, "AA".repeat(32))]), start_time);
+        let other_chain = ChainSpec {
+            validators: vec![crate::testgen::ValidatorSpec {
+                id: "z".to_string(),
+                power: 10,
+                key_type: crate::testgen::KeyType::Ed25519,
+            }],
+            ..ChainSpec::default()
+        }
+        .generate()
+        .unwrap();
+
+        let primary = spawn_mock_rpc(MockChain::from(&honest)).await;
+        let witnesses = [
+            spawn_mock_rpc(MockChain::from(&honest)).await,
+            spawn_mock_rpc(MockChain::from(&forked)).await,
+            spawn_mock_rpc(MockChain::from(&other_chain)).await,
+        ];
+
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        let check = cross_check(
+            primary.parse().unwrap(),
+            witnesses.iter().map(|url| url.parse().unwrap()).collect(),
+            chain_client_state(&honest),
+            &client_id,
+            &honest.consensus_state(2).unwrap(),
+            honest.ibc_height(2).unwrap(),
+            5,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            check.header.signed_header,
+            honest.header(5, 2).unwrap().signed_header
+        );
+        assert!(matches!(check.witnesses[0].1, WitnessReport::Agrees));
+        assert!(matches!(check.witnesses[1].1, WitnessReport::Conflict(_)));
+        assert!(matches!(check.witnesses[2].1, WitnessReport::Faulty(_)));
+
+        let conflicts: Vec<_> = check.conflicts().collect();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].header2().signed_header,
+            forked.header(5, 2).unwrap().signed_header
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Builds the cdylib and `tests/ffi/harness.c` against it, then runs the
+//! harness on a generated chain and the Osmosis proof in `src/data/proof.json`.
+#![cfg(all(feature = "ffi", unix))]
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    time::Duration,
+};
+
+use ibc_core::{
+    channel::types::{commitment::compute_packet_commitment, timeout::TimeoutHeight},
+    client::types::Height,
+    host::types::{
+        identifiers::{ChannelId, PortId, Sequence},
+        path::CommitmentPath,
+    },
+    primitives::{proto::Protobuf, Timestamp},
+};
+use ibc_proto::ibc::lightclients::tendermint::v1::Header as RawHeader;
+use serde_json::{json, Value};
+use tendermint::Time;
+use tendermint_lightclient::{
+    testgen::ChainSpec,
+    utils::{base64_to_bytes, ConsensusStateFile},
+};
+
+/// Builds the library as a cdylib with the `ffi` feature, the way the README
+/// does, and returns its path.
+fn build_cdylib(manifest_dir: &Path) -> PathBuf {
+    let output = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .args([
+            "rustc",
+            "--lib",
+            "--features",
+            "ffi",
+            "--crate-type",
+            "cdylib",
+        ])
+        .arg("--message-format=json")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .flat_map(|message| message["filenames"].as_array().cloned().unwrap_or_default())
+        .filter_map(|file| file.as_str().map(PathBuf::from))
+        .find(|file| {
+            file.file_stem()
+                .is_some_and(|stem| stem == "libtendermint_lightclient")
+                && file.extension().is_some_and(|ext| ext != "rlib")
+        })
+        .expect("cargo built no cdylib")
+}
+
+#[test]
+fn c_harness() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let proof: Value = serde_json::from_str(
+        &fs::read_to_string(manifest_dir.join("src/data/proof.json")).unwrap(),
+    )
+    .unwrap();
+    let field = |name: &str| base64_to_bytes(proof[name].as_str().unwrap());
+
+    // block 6 carries the app hash the proof is against
+    let chain = ChainSpec {
+        start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+        app_hashes: BTreeMap::from([(6, hex::encode_upper(field("root")))]),
+        ..ChainSpec::default()
+    }
+    .generate()
+    .unwrap();
+
+    let dir = env::temp_dir().join(format!("tendermint-lightclient-ffi-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let config = json!({
+        "chain_id": "testgen-0",
+        "trusted_height": "0-2",
+        "consensus_state": ConsensusStateFile::from(chain.consensus_state(2).unwrap()),
+        "trusting_period": "14d",
+    });
+    fs::write(dir.join("config.json"), config.to_string()).unwrap();
+    let header = chain.header(6, 2).unwrap();
+    fs::write(
+        dir.join("header.pb"),
+        Protobuf::<RawHeader>::encode_vec(header),
+    )
+    .unwrap();
+    fs::write(dir.join("proof.pb"), field("proof_commitment")).unwrap();
+    let value = compute_packet_commitment(
+        &field("data"),
+        &TimeoutHeight::At(Height::new(4, 21413739).unwrap()),
+        &Timestamp::from_nanoseconds(0).unwrap(),
+    );
+    fs::write(dir.join("value"), value.into_vec()).unwrap();
+    let path = CommitmentPath::new(
+        &PortId::transfer(),
+        &ChannelId::new(0),
+        Sequence::from(3514632),
+    );
+
+    let cdylib = build_cdylib(manifest_dir);
+    let lib_dir = cdylib.parent().unwrap();
+    let harness = dir.join("harness");
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests/ffi/harness.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-ltendermint_lightclient")
+        .arg("-o")
+        .arg(&harness)
+        .status()
+        .unwrap();
+    assert!(status.success(), "failed to compile the harness");
+
+    // cargo's library path can hold a cdylib built without `ffi`, it would
+    // shadow the rpath
+    let output = Command::new(&harness)
+        .env_remove("LD_LIBRARY_PATH")
+        .arg(dir.join("config.json"))
+        .arg(dir.join("header.pb"))
+        .arg(dir.join("proof.pb"))
+        .arg(path.to_string())
+        .arg(dir.join("value"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,95 @@
+/*
+ * Drives the C ABI the way an embedding service does, run by tests/ffi.rs:
+ *
+ *   harness <config.json> <header.pb> <proof.pb> <path> <value>
+ *
+ * The header is at height 0-6 and the proof of `value` at `path` is against
+ * its app hash. Exits non-zero on the first failed check.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "tendermint_lightclient.h"
+
+#define CHECK(cond)                                                                      \
+    do {                                                                                 \
+        if (!(cond)) {                                                                   \
+            const char *error = tmlc_last_error();                                       \
+            fprintf(stderr, "%s:%d: check failed: %s (last error: %s)\n", __FILE__,      \
+                    __LINE__, #cond, error ? error : "none");                            \
+            exit(1);                                                                     \
+        }                                                                                \
+    } while (0)
+
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *file = fopen(path, "rb");
+    CHECK(file != NULL);
+    fseek(file, 0, SEEK_END);
+    *len = (size_t)ftell(file);
+    fseek(file, 0, SEEK_SET);
+    uint8_t *data = malloc(*len + 1);
+    CHECK(data != NULL);
+    CHECK(fread(data, 1, *len, file) == *len);
+    data[*len] = 0;
+    fclose(file);
+    return data;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 6);
+    size_t config_len, header_len, proof_len, value_len;
+    char *config = (char *)read_file(argv[1], &config_len);
+    uint8_t *header = read_file(argv[2], &header_len);
+    uint8_t *proof = read_file(argv[3], &proof_len);
+    const char *path = argv[4];
+    uint8_t *value = read_file(argv[5], &value_len);
+
+    TmlcClient *client = NULL;
+    CHECK(tmlc_client_new(NULL, &client) == TMLC_ERR_NULL_POINTER);
+    CHECK(tmlc_client_new(config, NULL) == TMLC_ERR_NULL_POINTER);
+    CHECK(tmlc_client_new("{}", &client) == TMLC_ERR_INVALID_ARGUMENT);
+    CHECK(client == NULL);
+    CHECK(strstr(tmlc_last_error(), "chain_id") != NULL);
+
+    CHECK(tmlc_client_new(config, &client) == TMLC_OK);
+    CHECK(client != NULL);
+    CHECK(tmlc_last_error() == NULL);
+
+    TmlcStatus status;
+    CHECK(tmlc_client_status(client, &status) == TMLC_OK);
+    CHECK(status.status == TMLC_STATUS_ACTIVE);
+    CHECK(status.latest_height.revision_height == 2);
+
+    uint8_t garbage[] = {0xff, 0xff, 0xff};
+    CHECK(tmlc_client_update(client, garbage, sizeof garbage, NULL) ==
+          TMLC_ERR_INVALID_ARGUMENT);
+    TmlcHeight height = {0, 0};
+    CHECK(tmlc_client_update(client, header, header_len, &height) == TMLC_OK);
+    CHECK(height.revision_number == 0 && height.revision_height == 6);
+    CHECK(tmlc_client_status(client, &status) == TMLC_OK);
+    CHECK(status.latest_height.revision_height == 6);
+
+    CHECK(tmlc_client_verify_membership(client, height, "ibc", path, proof, proof_len, value,
+                                        value_len) == TMLC_OK);
+    const uint8_t forged[] = "forged";
+    CHECK(tmlc_client_verify_membership(client, height, "ibc", path, proof, proof_len, forged,
+                                        sizeof forged - 1) == TMLC_ERR_VERIFICATION);
+    CHECK(tmlc_client_verify_non_membership(client, height, "ibc", path, proof, proof_len) ==
+          TMLC_ERR_VERIFICATION);
+    CHECK(tmlc_client_verify_membership(client, height, "ibc", "not a path", proof, proof_len,
+                                        value, value_len) == TMLC_ERR_INVALID_ARGUMENT);
+    TmlcHeight missing = {0, 5};
+    CHECK(tmlc_client_verify_membership(client, missing, "ibc", path, proof, proof_len, value,
+                                        value_len) == TMLC_ERR_VERIFICATION);
+
+    tmlc_client_free(client);
+    tmlc_client_free(NULL);
+    free(config);
+    free(header);
+    free(proof);
+    free(value);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,181 @@
+//! Runs the fetch, bisection and follow flows of a light client against the
+//! mock RPC server, the way they run against a full node.
+#![cfg(feature = "std")]
+
+use std::{env, fs, process, time::Duration};
+
+use ibc_core::{
+    client::types::Height,
+    host::types::identifiers::{ChainId, ClientId},
+};
+use tendermint::Time;
+use tendermint_lightclient::{
+    client_options::ClientOptions,
+    context::Ctx,
+    light_client::LightClient,
+    mock_rpc::{self, MockChain},
+    provider::LightClientProvider,
+    testgen::{ChainSpec, KeyType, TestChain, ValidatorChange, ValidatorSpec},
+    utils::read_consensus_state,
+};
+use tokio::{net::TcpListener, process::Command};
+
+fn validators(ids: &[&str]) -> Vec<ValidatorSpec> {
+    ids.iter()
+        .map(|id| ValidatorSpec {
+            id: id.to_string(),
+            power: 10,
+            key_type: KeyType::Ed25519,
+        })
+        .collect()
+}
+
+/// Ten blocks whose validators are replaced half at a time at heights 4 and
+/// 7, so block 10 has none of the validators of block 1.
+fn chain() -> TestChain {
+    ChainSpec {
+        start_time: (Time::now() - Duration::from_secs(3600)).unwrap(),
+        changes: vec![
+            ValidatorChange {
+                height: 4,
+                validators: validators(&["a", "b", "x", "y"]),
+            },
+            ValidatorChange {
+                height: 7,
+                validators: validators(&["x", "y", "z", "w"]),
+            },
+        ],
+        ..ChainSpec::default()
+    }
+    .generate()
+    .unwrap()
+}
+
+async fn spawn(chain: &TestChain) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(mock_rpc::serve(listener, MockChain::from(chain)));
+    url
+}
+
+/// Client trusting `chain` at `height`.
+fn client(chain: &TestChain, height: u64) -> LightClient {
+    let client_state = ClientOptions {
+        chain_id: ChainId::new(&chain.spec().chain_id).unwrap(),
+        ..ClientOptions::default()
+    }
+    .client_state(chain.ibc_height(height).unwrap())
+    .unwrap();
+    LightClient::initialise(
+        Ctx::default(),
+        ClientId::new("stand-alone", 0).unwrap(),
+        client_state,
+        chain.consensus_state(height).unwrap(),
+    )
+    .unwrap()
+}
+
+fn latest_height(client: &LightClient) -> Height {
+    client.client_state().unwrap().inner().latest_height
+}
+
+#[tokio::test]
+async fn fetch_verify_and_update() {
+    let chain = chain();
+    let url = spawn(&chain).await;
+    let dir = env::temp_dir().join(format!("tendermint-lightclient-fetch-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let run = |args: Vec<String>| async move {
+        let output = Command::new(env!("CARGO_BIN_EXE_tendermint-lightclient"))
+            .args(["--chain-id", "testgen-0"])
+            .args(&args)
+            .output()
+            .await
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{args:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+
+    // `fetch-consensus-state` fetches block 6, `fetch-header` headers trust it
+    run(vec![
+        "fetch-consensus-state".into(),
+        url.clone(),
+        path("cs.json"),
+    ])
+    .await;
+    run(vec![
+        "fetch-header".into(),
+        url.clone(),
+        "8".into(),
+        path("header.json"),
+    ])
+    .await;
+    run(vec!["verify".into(), path("cs.json"), path("header.json")]).await;
+    run(vec![
+        "update".into(),
+        path("cs.json"),
+        path("header.json"),
+        path("new_cs.json"),
+    ])
+    .await;
+
+    assert_eq!(
+        read_consensus_state(&path("cs.json")).unwrap(),
+        chain.consensus_state(6).unwrap()
+    );
+    assert_eq!(
+        read_consensus_state(&path("new_cs.json")).unwrap(),
+        chain.consensus_state(8).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn bisects_to_target() {
+    let chain = chain();
+    let provider = LightClientProvider::new(spawn(&chain).await.parse().unwrap());
+    let mut client = client(&chain, 1);
+
+    // none of the trusted validators signed block 10
+    let header = provider
+        .header_trusting(10, latest_height(&client))
+        .await
+        .unwrap();
+    assert!(client.update(header).is_err());
+
+    // halve the distance until a header verifies, then aim at the target again
+    let verified = provider.bisect(&mut client, 10).await.unwrap();
+    assert_eq!(
+        verified,
+        [chain.ibc_height(5).unwrap(), chain.ibc_height(10).unwrap()]
+    );
+    assert_eq!(
+        client
+            .consensus_state(chain.ibc_height(10).unwrap())
+            .unwrap()
+            .inner(),
+        &chain.consensus_state(10).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn follows_head() {
+    let chain = chain();
+    let provider = LightClientProvider::new(spawn(&chain).await.parse().unwrap());
+    let mut client = client(&chain, 1);
+
+    let head = provider.latest_height().await.unwrap();
+    assert_eq!(head, 10);
+    for height in latest_height(&client).revision_height() + 1..=head {
+        let header = provider
+            .header_trusting(height.try_into().unwrap(), latest_height(&client))
+            .await
+            .unwrap();
+        client.update(header).unwrap();
+    }
+    assert_eq!(latest_height(&client), chain.ibc_height(head).unwrap());
+}